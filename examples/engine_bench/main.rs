@@ -0,0 +1,162 @@
+// Native timings of the rules engine behind `set_piece`, next to the engine
+// it replaced, which previous.rs keeps as it was:
+//
+//     cargo run --release --example engine_bench
+//
+// These are wall-clock times on the host, not Stylus gas. Gas has to be
+// measured against a Stylus node, e.g. with `cargo stylus deploy` on a
+// local devnode followed by `cast estimate` on `setPiece`.
+mod previous;
+
+use std::time::{Duration, Instant};
+
+use stylish_go::board::Board;
+use stylish_go::constants::{BLACK, KO_SIMPLE, WHITE};
+use stylish_go::position::{Point, Position};
+
+const RUNS: usize = 8;
+
+// What `set_piece` asks of either engine, under simple ko.
+trait Engine: Clone {
+    fn empty(size: u8) -> Self;
+    fn is_empty(&self, x: u8, y: u8) -> bool;
+    fn is_legal(&self, color: u8, x: u8, y: u8) -> bool;
+    // Stones captured, or None if the move is illegal.
+    fn play(&mut self, color: u8, x: u8, y: u8) -> Option<u32>;
+    fn pass(&mut self, color: u8);
+}
+
+impl Engine for Position<'static> {
+    fn empty(size: u8) -> Self {
+        Position::new(Board::empty(size), KO_SIMPLE, BLACK)
+    }
+
+    fn is_empty(&self, x: u8, y: u8) -> bool {
+        self.board.get_stone_at_position(x, y) == 0
+    }
+
+    fn is_legal(&self, color: u8, x: u8, y: u8) -> bool {
+        Position::is_legal(self, color, Point::new(x, y))
+    }
+
+    fn play(&mut self, color: u8, x: u8, y: u8) -> Option<u32> {
+        Position::play(self, color, Point::new(x, y)).ok().map(|outcome| outcome.captured)
+    }
+
+    fn pass(&mut self, color: u8) {
+        Position::pass(self, color)
+    }
+}
+
+impl Engine for previous::Position {
+    fn empty(size: u8) -> Self {
+        previous::Position { board: previous::Board::empty(size), ko: None }
+    }
+
+    fn is_empty(&self, x: u8, y: u8) -> bool {
+        self.board.get_stone_at_position(x, y) == 0
+    }
+
+    fn is_legal(&self, color: u8, x: u8, y: u8) -> bool {
+        self.try_play(color, x, y).is_some()
+    }
+
+    fn play(&mut self, color: u8, x: u8, y: u8) -> Option<u32> {
+        let (next, captured) = self.try_play(color, x, y)?;
+        *self = next;
+        Some(captured)
+    }
+
+    fn pass(&mut self, _color: u8) {
+        self.ko = None;
+    }
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// The first legal empty point from a seeded start, so both engines make
+// the same moves.
+fn random_point(engine: &impl Engine, color: u8, seed: u64) -> Option<(u8, u8)> {
+    let empty: Vec<(u8, u8)> = (0..19)
+        .flat_map(|y| (0..19).map(move |x| (x, y)))
+        .filter(|&(x, y)| engine.is_empty(x, y))
+        .collect();
+    let start = splitmix64(seed) as usize;
+    (0..empty.len())
+        .map(|offset| empty[(start + offset) % empty.len()])
+        .find(|&(x, y)| engine.is_legal(color, x, y))
+}
+
+fn random_turn(engine: &mut impl Engine, color: u8, seed: u64) -> u64 {
+    match random_point(engine, color, seed) {
+        Some((x, y)) => engine.play(color, x, y).unwrap_or(0) as u64,
+        None => {
+            engine.pass(color);
+            0
+        }
+    }
+}
+
+// Fastest of several runs, which is the least disturbed by the host.
+fn fastest(mut run: impl FnMut() -> u64) -> (Duration, u64) {
+    let mut best = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = run();
+        best = best.min(start.elapsed());
+    }
+    (best, result)
+}
+
+// Random legal play; every move finds groups, liberties and captures.
+fn random_games<E: Engine>(games: u64, moves: u32) -> u64 {
+    let mut captured = 0;
+    for game in 0..games {
+        let mut engine = E::empty(19);
+        let mut color = BLACK;
+        for turn in 0..moves {
+            captured += random_turn(&mut engine, color, game << 32 | turn as u64);
+            color = if color == BLACK { WHITE } else { BLACK };
+        }
+    }
+    captured
+}
+
+// Every point tried for both colours on a crowded board, as the bots do.
+fn legality_scan<E: Engine>(rounds: u64) -> u64 {
+    let mut engine = E::empty(19);
+    let mut color = BLACK;
+    for turn in 0..200 {
+        random_turn(&mut engine, color, turn);
+        color = if color == BLACK { WHITE } else { BLACK };
+    }
+
+    let mut legal = 0;
+    for _ in 0..rounds {
+        for y in 0..19 {
+            for x in 0..19 {
+                legal += engine.is_legal(BLACK, x, y) as u64;
+                legal += engine.is_legal(WHITE, x, y) as u64;
+            }
+        }
+    }
+    legal
+}
+
+fn main() {
+    let (before, expected) = fastest(|| random_games::<previous::Position>(200, 250));
+    let (after, captured) = fastest(|| random_games::<Position>(200, 250));
+    assert_eq!(captured, expected, "the engines disagree");
+    println!("random play, 200 games of 250 moves: {before:?} -> {after:?} ({captured} stones captured)");
+
+    let (before, expected) = fastest(|| legality_scan::<previous::Position>(500));
+    let (after, legal) = fastest(|| legality_scan::<Position>(500));
+    assert_eq!(legal, expected, "the engines disagree");
+    println!("legality scan, 500 rounds on a crowded board: {before:?} -> {after:?} ({legal} legal moves)");
+}
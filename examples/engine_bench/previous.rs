@@ -0,0 +1,336 @@
+// The rules engine as it was before colour bitplanes, kept only to time
+// against the current one. Boards are the two-bit storage words themselves,
+// and groups and liberties are found point by point.
+use stylus_sdk::alloy_primitives::U256;
+
+use stylish_go::constants::{BOARD_WORDS, MAX_POINTS, NO_KO, POINTS_PER_WORD};
+
+// Points are stored row by row from the top-left corner, two bits each, and
+// spill over into the next word every 128 points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Board {
+    pub size: u8,
+    pub words: [U256; BOARD_WORDS],
+}
+
+impl Board {
+    pub fn empty(size: u8) -> Self {
+        Board {
+            size,
+            words: [U256::ZERO; BOARD_WORDS],
+        }
+    }
+
+    pub fn get_stone_at_position(&self, x: u8, y: u8) -> u8 {
+        if !self.is_valid_position(x, y) {
+            return 0;
+        }
+
+        let position = y as usize * self.size as usize + x as usize;
+        let limbs = self.words[position / POINTS_PER_WORD].as_limbs();
+        let offset = position % POINTS_PER_WORD;
+
+        ((limbs[offset / 32] >> ((offset % 32) * 2)) & 0b11) as u8
+    }
+
+    pub fn set_stone_at_position(&self, x: u8, y: u8, stone: u8) -> Board {
+        if !self.is_valid_position(x, y) {
+            return *self;
+        }
+
+        let position = y as usize * self.size as usize + x as usize;
+        let word = position / POINTS_PER_WORD;
+        let offset = position % POINTS_PER_WORD;
+        let shift = (offset % 32) * 2;
+
+        let mut limbs = self.words[word].into_limbs();
+        limbs[offset / 32] = (limbs[offset / 32] & !(0b11u64 << shift)) | ((stone as u64) << shift);
+
+        let mut updated_board = *self;
+        updated_board.words[word] = U256::from_limbs(limbs);
+        updated_board
+    }
+
+    pub fn is_valid_position(&self, x: u8, y: u8) -> bool {
+        x < self.size && y < self.size
+    }
+
+    pub fn count_liberties(&self, x: u8, y: u8) -> u32 {
+        let stone_color = self.get_stone_at_position(x, y);
+        if stone_color == 0 {
+            return 0;
+        }
+
+        let size = self.size as usize;
+        let mut visited = PointSet::default();
+        let mut liberty_set = PointSet::default();
+
+        let mut stack = Vec::with_capacity(size);
+        stack.push((x, y));
+
+        while let Some((curr_x, curr_y)) = stack.pop() {
+            let pos_idx = curr_y as usize * size + curr_x as usize;
+
+            if visited.contains(pos_idx) {
+                continue;
+            }
+
+            visited.insert(pos_idx);
+
+            // Right
+            if curr_x + 1 < self.size {
+                let nx = curr_x + 1;
+                let neighbor_stone = self.get_stone_at_position(nx, curr_y);
+                let neighbor_idx = curr_y as usize * size + nx as usize;
+
+                if neighbor_stone == 0 {
+                    liberty_set.insert(neighbor_idx);
+                } else if neighbor_stone == stone_color && !visited.contains(neighbor_idx) {
+                    stack.push((nx, curr_y));
+                }
+            }
+
+            // Down
+            if curr_y + 1 < self.size {
+                let ny = curr_y + 1;
+                let neighbor_stone = self.get_stone_at_position(curr_x, ny);
+                let neighbor_idx = ny as usize * size + curr_x as usize;
+
+                if neighbor_stone == 0 {
+                    liberty_set.insert(neighbor_idx);
+                } else if neighbor_stone == stone_color && !visited.contains(neighbor_idx) {
+                    stack.push((curr_x, ny));
+                }
+            }
+
+            // Left
+            if curr_x > 0 {
+                let nx = curr_x - 1;
+                let neighbor_stone = self.get_stone_at_position(nx, curr_y);
+                let neighbor_idx = curr_y as usize * size + nx as usize;
+
+                if neighbor_stone == 0 {
+                    liberty_set.insert(neighbor_idx);
+                } else if neighbor_stone == stone_color && !visited.contains(neighbor_idx) {
+                    stack.push((nx, curr_y));
+                }
+            }
+
+            // Up
+            if curr_y > 0 {
+                let ny = curr_y - 1;
+                let neighbor_stone = self.get_stone_at_position(curr_x, ny);
+                let neighbor_idx = ny as usize * size + curr_x as usize;
+
+                if neighbor_stone == 0 {
+                    liberty_set.insert(neighbor_idx);
+                } else if neighbor_stone == stone_color && !visited.contains(neighbor_idx) {
+                    stack.push((curr_x, ny));
+                }
+            }
+        }
+
+        liberty_set.count()
+    }
+
+    pub fn would_be_suicide(&self, x: u8, y: u8, stone_color: u8) -> bool {
+        if self.would_capture_opponent_stones(x, y, stone_color) {
+            return false;
+        }
+        let temp_board = self.set_stone_at_position(x, y, stone_color);
+        let liberties = temp_board.count_liberties(x, y);
+        liberties == 0
+    }
+
+    pub fn would_capture_opponent_stones(&self, x: u8, y: u8, stone_color: u8) -> bool {
+        let opponent_color = if stone_color == 1 { 2 } else { 1 };
+        let neighbors = [
+            (x + 1, y),     // Right
+            (x, y + 1),     // Down
+            (x.wrapping_sub(1), y), // Left
+            (x, y.wrapping_sub(1)), // Up
+        ];
+
+        for (nx, ny) in neighbors.iter() {
+            if !self.is_valid_position(*nx, *ny) {
+                continue;
+            }
+
+            let neighbor_stone = self.get_stone_at_position(*nx, *ny);
+            if neighbor_stone == opponent_color {
+                let opponent_liberties = self.count_liberties(*nx, *ny);
+                if opponent_liberties == 1 {
+                    let mut is_our_position_liberty = false;
+                    if self.get_stone_at_position(x, y) == 0 {
+                        for (check_x, check_y) in neighbors.iter() {
+                            if self.is_valid_position(*check_x, *check_y) &&
+                               *check_x == *nx && *check_y == *ny {
+                                is_our_position_liberty = true;
+                                break;
+                            }
+                        }
+                    }
+
+                    if is_our_position_liberty {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn capture_surrounded_stones(&self, x: u8, y: u8, stone_color: u8) -> (Board, u32, u8, u8) {
+        let opponent_color = if stone_color == 1 { 2 } else { 1 };
+        let mut captured_count = 0;
+        let mut updated_board = *self;
+        let mut last_captured = (NO_KO, NO_KO);
+
+        let neighbors = [
+            (x + 1, y),     // Right
+            (x, y + 1),     // Down
+            (x.wrapping_sub(1), y), // Left
+            (x, y.wrapping_sub(1)), // Up
+        ];
+
+        for (nx, ny) in neighbors.iter() {
+            if !self.is_valid_position(*nx, *ny) {
+                continue;
+            }
+
+            let neighbor_stone = updated_board.get_stone_at_position(*nx, *ny);
+            if neighbor_stone == opponent_color {
+                let liberties = updated_board.count_liberties(*nx, *ny);
+
+                if liberties == 0 {
+                    let (new_board, stones_removed, removed_positions) = updated_board.remove_group(*nx, *ny);
+                    updated_board = new_board;
+                    captured_count += stones_removed;
+
+                    if stones_removed == 1 && !removed_positions.is_empty() {
+                        last_captured = removed_positions[0];
+                    }
+                }
+            }
+        }
+
+        // A ko only arises when a lone stone captures exactly one stone and is
+        // left with a single liberty, i.e. the point it just emptied.
+        let is_lone_stone = neighbors.iter().all(|(nx, ny)| {
+            !self.is_valid_position(*nx, *ny) || updated_board.get_stone_at_position(*nx, *ny) != stone_color
+        });
+
+        if captured_count == 1 && is_lone_stone && updated_board.count_liberties(x, y) == 1 {
+            (updated_board, captured_count, last_captured.0, last_captured.1)
+        } else {
+            (updated_board, captured_count, NO_KO, NO_KO)
+        }
+    }
+
+    pub fn remove_group(&self, x: u8, y: u8) -> (Board, u32, Vec<(u8, u8)>) {
+        let stone_color = self.get_stone_at_position(x, y);
+        if stone_color == 0 {
+            return (*self, 0, Vec::new());
+        }
+
+        let size = self.size as usize;
+        let mut visited = PointSet::default();
+        let mut group_stones = PointSet::default();
+        let mut count = 0;
+        let mut removed_positions = Vec::new();
+
+        let mut stack = Vec::with_capacity(size);
+        stack.push((x, y));
+
+        while let Some((curr_x, curr_y)) = stack.pop() {
+            let pos_idx = curr_y as usize * size + curr_x as usize;
+
+            if visited.contains(pos_idx) {
+                continue;
+            }
+
+            visited.insert(pos_idx);
+
+            let curr_stone = self.get_stone_at_position(curr_x, curr_y);
+            if curr_stone != stone_color {
+                continue;
+            }
+
+            group_stones.insert(pos_idx);
+            count += 1;
+            removed_positions.push((curr_x, curr_y));
+
+            let directions = [
+                (curr_x + 1, curr_y),     // Right
+                (curr_x, curr_y + 1),     // Down
+                (curr_x.wrapping_sub(1), curr_y), // Left
+                (curr_x, curr_y.wrapping_sub(1)), // Up
+            ];
+
+            for (nx, ny) in directions.iter() {
+                if self.is_valid_position(*nx, *ny) {
+                    let next_stone = self.get_stone_at_position(*nx, *ny);
+                    if next_stone == stone_color {
+                        stack.push((*nx, *ny));
+                    }
+                }
+            }
+        }
+
+        let mut new_board = *self;
+
+        for y in 0..size {
+            for x in 0..size {
+                if group_stones.contains(y * size + x) {
+                    new_board = new_board.set_stone_at_position(x as u8, y as u8, 0);
+                }
+            }
+        }
+
+        (new_board, count, removed_positions)
+    }
+}
+
+const POINT_SET_LIMBS: usize = MAX_POINTS.div_ceil(64);
+
+// One bit per point index, large enough for the biggest supported board.
+#[derive(Clone, Copy, Default)]
+struct PointSet([u64; POINT_SET_LIMBS]);
+
+impl PointSet {
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1u64 << (index % 64)) != 0
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1u64 << (index % 64);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|limb| limb.count_ones()).sum()
+    }
+}
+
+// Position::try_play and play from the same engine, under simple ko only.
+#[derive(Clone)]
+pub struct Position {
+    pub board: Board,
+    pub ko: Option<(u8, u8)>,
+}
+
+impl Position {
+    pub fn try_play(&self, color: u8, x: u8, y: u8) -> Option<(Position, u32)> {
+        if !self.board.is_valid_position(x, y) || self.board.get_stone_at_position(x, y) != 0 {
+            return None;
+        }
+        if self.ko == Some((x, y)) || self.board.would_be_suicide(x, y, color) {
+            return None;
+        }
+
+        let placed = self.board.set_stone_at_position(x, y, color);
+        let (board, captured, ko_x, ko_y) = placed.capture_surrounded_stones(x, y, color);
+        let ko = if ko_x == NO_KO { None } else { Some((ko_x, ko_y)) };
+        Some((Position { board, ko }, captured))
+    }
+}
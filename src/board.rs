@@ -0,0 +1,546 @@
+use crate::constants::{BLACK, BOARD_WORDS, MAX_POINTS, NO_KO, POINTS_PER_WORD, SUPPORTED_BOARD_SIZES};
+
+const PLANE_LIMBS: usize = MAX_POINTS.div_ceil(64);
+
+// One bit per point index, row by row from the top-left corner. Neighbours
+// of every point in a plane are found at once by shifting the whole plane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Plane([u64; PLANE_LIMBS]);
+
+impl Plane {
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1u64 << (index % 64)) != 0
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1u64 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1u64 << (index % 64));
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|limb| limb.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|limb| *limb == 0)
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.0.iter()
+            .position(|limb| *limb != 0)
+            .map(|i| i * 64 + self.0[i].trailing_zeros() as usize)
+    }
+
+    // Every index in the plane, lowest first.
+    pub fn indices(self) -> impl Iterator<Item = usize> {
+        self.0.into_iter().enumerate().flat_map(|(i, mut limb)| {
+            core::iter::from_fn(move || {
+                let bit = limb.trailing_zeros() as usize;
+                limb &= limb.wrapping_sub(1);
+                (bit < 64).then_some(i * 64 + bit)
+            })
+        })
+    }
+
+    pub fn and(self, other: Plane) -> Plane {
+        let mut result = self;
+        for (limb, other) in result.0.iter_mut().zip(other.0) {
+            *limb &= other;
+        }
+        result
+    }
+
+    pub fn or(self, other: Plane) -> Plane {
+        let mut result = self;
+        for (limb, other) in result.0.iter_mut().zip(other.0) {
+            *limb |= other;
+        }
+        result
+    }
+
+    pub fn and_not(self, other: Plane) -> Plane {
+        let mut result = self;
+        for (limb, other) in result.0.iter_mut().zip(other.0) {
+            *limb &= !other;
+        }
+        result
+    }
+
+    // Moves every point `n` indices forward, i.e. right or down the board.
+    // Shifts are by one column or one row, so always less than a limb.
+    fn shift_up(self, n: usize) -> Plane {
+        let mut result = self;
+        for i in (1..PLANE_LIMBS).rev() {
+            result.0[i] = (self.0[i] << n) | (self.0[i - 1] >> (64 - n));
+        }
+        result.0[0] = self.0[0] << n;
+        result
+    }
+
+    // Moves every point `n` indices back, i.e. left or up the board.
+    fn shift_down(self, n: usize) -> Plane {
+        let mut result = self;
+        for i in 0..PLANE_LIMBS - 1 {
+            result.0[i] = (self.0[i] >> n) | (self.0[i + 1] << (64 - n));
+        }
+        result.0[PLANE_LIMBS - 1] = self.0[PLANE_LIMBS - 1] >> n;
+        result
+    }
+}
+
+// The points of a board and its left and right edges, which keep horizontal
+// shifts from wrapping into the neighbouring row.
+#[derive(Clone, Copy)]
+struct Masks {
+    points: Plane,
+    left_edge: Plane,
+    right_edge: Plane,
+}
+
+impl Masks {
+    const fn new(size: u8) -> Self {
+        let size = size as usize;
+        let mut masks = Masks {
+            points: Plane([0; PLANE_LIMBS]),
+            left_edge: Plane([0; PLANE_LIMBS]),
+            right_edge: Plane([0; PLANE_LIMBS]),
+        };
+        let mut index = 0;
+        while index < size * size {
+            masks.points.0[index / 64] |= 1 << (index % 64);
+            if index % size == 0 {
+                masks.left_edge.0[index / 64] |= 1 << (index % 64);
+            }
+            if index % size == size - 1 {
+                masks.right_edge.0[index / 64] |= 1 << (index % 64);
+            }
+            index += 1;
+        }
+        masks
+    }
+
+    fn for_size(size: u8) -> Self {
+        match SUPPORTED_BOARD_SIZES.iter().position(|supported| *supported == size) {
+            Some(i) => SUPPORTED_MASKS[i],
+            None => Masks::new(size),
+        }
+    }
+}
+
+const SUPPORTED_MASKS: [Masks; SUPPORTED_BOARD_SIZES.len()] = [
+    Masks::new(SUPPORTED_BOARD_SIZES[0]),
+    Masks::new(SUPPORTED_BOARD_SIZES[1]),
+    Masks::new(SUPPORTED_BOARD_SIZES[2]),
+    Masks::new(SUPPORTED_BOARD_SIZES[3]),
+];
+
+// Small boards only have room for the corner and centre star points.
+pub fn max_handicap(size: u8) -> u8 {
+    match size {
+        7 => 4,
+        9 => 5,
+        _ => 9,
+    }
+}
+
+// Even bits of a word hold the low bit of each 2-bit point.
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+
+// Packs the even bits of `x` into its low 32 bits.
+fn compact(mut x: u64) -> u64 {
+    x &= LOW_BITS;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x >> 4)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x >> 8)) & 0x0000_FFFF_0000_FFFF;
+    (x | (x >> 16)) & 0x0000_0000_FFFF_FFFF
+}
+
+// Inverse of `compact`: spreads the low 32 bits of `x` over the even bits.
+fn spread(mut x: u64) -> u64 {
+    x &= 0x0000_0000_FFFF_FFFF;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & LOW_BITS
+}
+
+// Stones are kept as one bitplane per colour. For storage they are packed two
+// bits per point, 128 points per word, see `from_words` and `to_words`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Board {
+    pub size: u8,
+    stones: [Plane; 2],
+}
+
+impl Board {
+    pub fn empty(size: u8) -> Self {
+        Board {
+            size,
+            stones: [Plane::default(); 2],
+        }
+    }
+
+    // Black stones on the star points in the usual order: corners, then for
+    // five or more stones the sides in pairs and the centre for odd counts.
+    // None for counts the board cannot take.
+    pub fn with_handicap(size: u8, stones: u8) -> Option<Self> {
+        if stones < 2 || stones > max_handicap(size) {
+            return None;
+        }
+        let near = if size >= 13 { 3 } else { 2 };
+        let far = size - 1 - near;
+        let middle = size / 2;
+        let corners = [(far, near), (near, far), (far, far), (near, near)];
+        let sides = [(near, middle), (far, middle), (middle, near), (middle, far)];
+
+        let count = stones as usize;
+        let (corners, sides) = if count <= 4 {
+            (&corners[..count], &sides[..0])
+        } else {
+            (&corners[..], &sides[..(count - 4) & !1])
+        };
+        let centre = if count >= 5 && count % 2 == 1 { Some((middle, middle)) } else { None };
+
+        let mut board = Board::empty(size);
+        for &(x, y) in corners.iter().chain(sides).chain(centre.iter()) {
+            board = board.set_stone_at_position(x, y, BLACK);
+        }
+        Some(board)
+    }
+
+    // Words are given as their four 64-bit limbs, least significant first.
+    // Each limb holds 32 consecutive points, which is half of a plane limb.
+    pub fn from_words(size: u8, words: [[u64; 4]; BOARD_WORDS]) -> Self {
+        let mut board = Board::empty(size);
+        for (word_index, word) in words.iter().enumerate() {
+            for (limb_index, limb) in word.iter().enumerate() {
+                let quarter = word_index * POINTS_PER_WORD / 32 + limb_index;
+                if quarter / 2 >= PLANE_LIMBS {
+                    continue;
+                }
+                let shift = 32 * (quarter % 2);
+                board.stones[0].0[quarter / 2] |= compact(*limb) << shift;
+                board.stones[1].0[quarter / 2] |= compact(*limb >> 1) << shift;
+            }
+        }
+        board
+    }
+
+    pub fn to_words(&self) -> [[u64; 4]; BOARD_WORDS] {
+        let mut words = [[0u64; 4]; BOARD_WORDS];
+        for (word_index, word) in words.iter_mut().enumerate() {
+            for (limb_index, limb) in word.iter_mut().enumerate() {
+                let quarter = word_index * POINTS_PER_WORD / 32 + limb_index;
+                if quarter / 2 >= PLANE_LIMBS {
+                    continue;
+                }
+                let shift = 32 * (quarter % 2);
+                *limb = spread(self.stones[0].0[quarter / 2] >> shift) |
+                        spread(self.stones[1].0[quarter / 2] >> shift) << 1;
+            }
+        }
+        words
+    }
+
+    pub fn index(&self, x: u8, y: u8) -> usize {
+        y as usize * self.size as usize + x as usize
+    }
+
+    pub fn point(&self, index: usize) -> (u8, u8) {
+        ((index % self.size as usize) as u8, (index / self.size as usize) as u8)
+    }
+
+    // Stones of colour 1 or 2.
+    pub fn stones(&self, color: u8) -> Plane {
+        self.stones[color as usize - 1]
+    }
+
+    pub fn empty_points(&self) -> Plane {
+        Masks::for_size(self.size).points.and_not(self.stones[0].or(self.stones[1]))
+    }
+
+    pub fn get_stone_at_position(&self, x: u8, y: u8) -> u8 {
+        if !self.is_valid_position(x, y) {
+            return 0;
+        }
+
+        let index = self.index(x, y);
+        if self.stones[0].contains(index) {
+            1
+        } else if self.stones[1].contains(index) {
+            2
+        } else {
+            0
+        }
+    }
+
+    pub fn set_stone_at_position(&self, x: u8, y: u8, stone: u8) -> Board {
+        if !self.is_valid_position(x, y) {
+            return *self;
+        }
+
+        let index = self.index(x, y);
+        let mut updated_board = *self;
+        updated_board.stones[0].remove(index);
+        updated_board.stones[1].remove(index);
+        if stone != 0 {
+            updated_board.stones[stone as usize - 1].insert(index);
+        }
+        updated_board
+    }
+
+    pub fn is_valid_position(&self, x: u8, y: u8) -> bool {
+        x < self.size && y < self.size
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.empty_points().is_empty()
+    }
+
+    // Cheaper than `neighbours` for a single point.
+    pub fn point_neighbours(&self, x: u8, y: u8) -> Plane {
+        let mut plane = Plane::default();
+        let index = self.index(x, y);
+        let size = self.size as usize;
+        if x + 1 < self.size {
+            plane.insert(index + 1);
+        }
+        if x > 0 {
+            plane.insert(index - 1);
+        }
+        if y + 1 < self.size {
+            plane.insert(index + size);
+        }
+        if y > 0 {
+            plane.insert(index - size);
+        }
+        plane
+    }
+
+    pub fn neighbours(&self, plane: Plane) -> Plane {
+        self.neighbours_within(plane, &Masks::for_size(self.size))
+    }
+
+    fn neighbours_within(&self, plane: Plane, masks: &Masks) -> Plane {
+        let size = self.size as usize;
+        plane.shift_up(1).and_not(masks.left_edge)
+            .or(plane.shift_down(1).and_not(masks.right_edge))
+            .or(plane.shift_up(size))
+            .or(plane.shift_down(size))
+            .and(masks.points)
+    }
+
+    // Grows `seed` through `within` until it stops changing.
+    pub fn flood_fill(&self, seed: Plane, within: Plane) -> Plane {
+        let masks = Masks::for_size(self.size);
+        let mut region = seed.and(within);
+        loop {
+            let grown = region.or(self.neighbours_within(region, &masks)).and(within);
+            if grown == region {
+                return region;
+            }
+            region = grown;
+        }
+    }
+
+    pub fn group(&self, x: u8, y: u8) -> Plane {
+        let stone_color = self.get_stone_at_position(x, y);
+        if stone_color == 0 {
+            return Plane::default();
+        }
+
+        let mut seed = Plane::default();
+        seed.insert(self.index(x, y));
+        self.flood_fill(seed, self.stones(stone_color))
+    }
+
+    pub fn liberties(&self, group: Plane) -> Plane {
+        let masks = Masks::for_size(self.size);
+        let occupied = self.stones[0].or(self.stones[1]);
+        self.neighbours_within(group, &masks).and_not(occupied)
+    }
+
+    pub fn count_liberties(&self, x: u8, y: u8) -> u32 {
+        self.liberties(self.group(x, y)).count()
+    }
+
+    pub fn would_be_suicide(&self, x: u8, y: u8, stone_color: u8) -> bool {
+        if self.would_capture_opponent_stones(x, y, stone_color) {
+            return false;
+        }
+        let temp_board = self.set_stone_at_position(x, y, stone_color);
+        let liberties = temp_board.count_liberties(x, y);
+        liberties == 0
+    }
+
+    pub fn would_capture_opponent_stones(&self, x: u8, y: u8, stone_color: u8) -> bool {
+        if self.get_stone_at_position(x, y) != 0 {
+            return false;
+        }
+        let temp_board = self.set_stone_at_position(x, y, stone_color);
+        !temp_board.captured_by(x, y, stone_color).is_empty()
+    }
+
+    // Opponent stones next to (x, y) whose groups have no liberties left.
+    fn captured_by(&self, x: u8, y: u8, stone_color: u8) -> Plane {
+        let opponent_color = if stone_color == 1 { 2 } else { 1 };
+
+        let mut captured = Plane::default();
+        let mut candidates = self.point_neighbours(x, y).and(self.stones(opponent_color));
+        while let Some(index) = candidates.first() {
+            let (nx, ny) = self.point(index);
+            let group = self.group(nx, ny);
+            if self.liberties(group).is_empty() {
+                captured = captured.or(group);
+            }
+            candidates = candidates.and_not(group);
+        }
+        captured
+    }
+
+    pub fn capture_surrounded_stones(&self, x: u8, y: u8, stone_color: u8) -> (Board, u32, u8, u8) {
+        let opponent_color = if stone_color == 1 { 2 } else { 1 };
+        let captured = self.captured_by(x, y, stone_color);
+        let captured_count = captured.count();
+
+        let mut updated_board = *self;
+        updated_board.stones[opponent_color as usize - 1] = self.stones(opponent_color).and_not(captured);
+
+        // A ko only arises when a lone stone captures exactly one stone and is
+        // left with a single liberty, i.e. the point it just emptied.
+        let is_lone_stone = self.point_neighbours(x, y).and(self.stones(stone_color)).is_empty();
+
+        match captured.first() {
+            Some(index) if captured_count == 1 && is_lone_stone && updated_board.count_liberties(x, y) == 1 => {
+                let (ko_x, ko_y) = self.point(index);
+                (updated_board, captured_count, ko_x, ko_y)
+            }
+            _ => (updated_board, captured_count, NO_KO, NO_KO),
+        }
+    }
+
+    pub fn remove_group(&self, x: u8, y: u8) -> (Board, u32) {
+        let stone_color = self.get_stone_at_position(x, y);
+        if stone_color == 0 {
+            return (*self, 0);
+        }
+
+        let group = self.group(x, y);
+        let mut new_board = *self;
+        new_board.stones[stone_color as usize - 1] = self.stones(stone_color).and_not(group);
+        (new_board, group.count())
+    }
+
+    // Rows are listed from the top edge; 'X' is colour 1 and 'O' is colour 2.
+    #[cfg(test)]
+    pub(crate) fn from_rows(size: u8, rows: &[&str]) -> Board {
+        let mut board = Board::empty(size);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.split_whitespace().enumerate() {
+                let stone = match cell {
+                    "X" => 1,
+                    "O" => 2,
+                    _ => 0,
+                };
+                board = board.set_stone_at_position(x as u8, y as u8, stone);
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stones_across_word_boundaries_on_19x19() {
+        // Index 127 and 128 sit on either side of the first word boundary,
+        // and (18, 18) is the last point of the third word.
+        let mut board = Board::empty(19);
+        board = board.set_stone_at_position(13, 6, 1);
+        board = board.set_stone_at_position(14, 6, 1);
+        board = board.set_stone_at_position(18, 18, 2);
+
+        assert_eq!(board.get_stone_at_position(13, 6), 1);
+        assert_eq!(board.get_stone_at_position(14, 6), 1);
+        assert_eq!(board.get_stone_at_position(18, 18), 2);
+        assert_eq!(board.count_liberties(13, 6), 6);
+        assert_eq!(board.count_liberties(18, 18), 2);
+
+        let (board, removed) = board.remove_group(14, 6);
+        assert_eq!(removed, 2);
+        assert_eq!(board.get_stone_at_position(13, 6), 0);
+        assert_eq!(board.get_stone_at_position(18, 18), 2);
+    }
+
+    #[test]
+    fn handicap_stones_sit_on_star_points() {
+        let board = Board::with_handicap(19, 9).unwrap();
+        for (x, y) in [(3, 3), (9, 3), (15, 3), (3, 9), (9, 9), (15, 9), (3, 15), (9, 15), (15, 15)] {
+            assert_eq!(board.get_stone_at_position(x, y), BLACK);
+        }
+
+        let board = Board::with_handicap(13, 3).unwrap();
+        assert_eq!(board.stones(BLACK).count(), 3);
+        assert_eq!(board.get_stone_at_position(6, 6), 0);
+        assert_eq!(board.get_stone_at_position(3, 3), 0);
+
+        let board = Board::with_handicap(9, 5).unwrap();
+        assert_eq!(board.get_stone_at_position(4, 4), BLACK);
+
+        assert_eq!(Board::with_handicap(9, 6), None);
+        assert_eq!(Board::with_handicap(7, 1), None);
+    }
+
+    #[test]
+    fn storage_words_round_trip() {
+        let board = Board::from_rows(19, &[
+            "X O . . . . . . . . . . . . . . . . O",
+            ". . . . . . . . . . . . . . . . . . X",
+        ]);
+        let board = board
+            .set_stone_at_position(13, 6, 2)
+            .set_stone_at_position(14, 6, 1)
+            .set_stone_at_position(18, 18, 2);
+
+        let words = board.to_words();
+        // Point (1, 0) is index 1, so colour 2 sets the high bit of its pair.
+        assert_eq!(words[0][0] & 0b1111, 0b1001);
+        // Index 128 is (14, 6), the first point of the second word.
+        assert_eq!(words[1][0] & 0b11, 1);
+        // Index 360 is (18, 18), point 104 of the third word.
+        assert_eq!(words[2][3] >> 16 & 0b11, 2);
+        assert_eq!(Board::from_words(19, words), board);
+    }
+
+    #[test]
+    fn plane_indices_are_listed_across_limbs() {
+        let mut plane = Plane::default();
+        for index in [0, 63, 64, 200, MAX_POINTS - 1] {
+            plane.insert(index);
+        }
+        assert_eq!(plane.indices().collect::<Vec<_>>(), [0, 63, 64, 200, MAX_POINTS - 1]);
+        assert_eq!(Plane::default().indices().next(), None);
+    }
+
+    #[test]
+    fn horizontal_neighbours_do_not_wrap_between_rows() {
+        let board = Board::from_rows(7, &[
+            ". . . . . . X",
+            "O . . . . . .",
+        ]);
+        assert_eq!(board.count_liberties(6, 0), 2);
+        assert_eq!(board.count_liberties(0, 1), 3);
+
+        let board = board.set_stone_at_position(5, 0, 2).set_stone_at_position(6, 1, 2);
+        let (board, captured, _, _) = board.capture_surrounded_stones(6, 1, 2);
+        assert_eq!(captured, 1);
+        assert_eq!(board.get_stone_at_position(6, 0), 0);
+    }
+}
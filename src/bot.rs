@@ -0,0 +1,323 @@
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::constants::RULES_AREA;
+use crate::position::{Point, Position};
+use crate::zobrist::splitmix64;
+
+// Upper bound on trial moves per turn, which keeps the bot's cost flat
+// however crowded the board gets.
+pub const MAX_EVALUATIONS: u32 = 64;
+
+// Each search candidate also scores the board, so fewer of them fit.
+pub const SEARCH_CANDIDATES: u32 = 24;
+
+// Every point ordered by ring, starting at the centre and moving outwards.
+pub fn spiral_order(size: u8) -> Vec<Point> {
+    let center = size / 2;
+    let last = size - 1;
+    let mut points = Vec::with_capacity(size as usize * size as usize);
+
+    for radius in 0..size {
+        let low_x = center.saturating_sub(radius);
+        let high_x = center.saturating_add(radius).min(last);
+        let low_y = low_x;
+        let high_y = high_x;
+        for y in low_y..=high_y {
+            for x in low_x..=high_x {
+                let on_ring = x == low_x || x == high_x || y == low_y || y == high_y;
+                let inside_previous = radius > 0 &&
+                    x >= center.saturating_sub(radius - 1) && x <= center.saturating_add(radius - 1).min(last) &&
+                    y >= center.saturating_sub(radius - 1) && y <= center.saturating_add(radius - 1).min(last);
+                if on_ring && !inside_previous {
+                    points.push(Point::new(x, y));
+                }
+            }
+        }
+    }
+
+    points
+}
+
+// The first legal point spiralling out from the centre.
+pub fn spiral_move(position: &Position, color: u8) -> Option<Point> {
+    spiral_order(position.board.size)
+        .into_iter()
+        .find(|point| position.is_legal(color, *point))
+}
+
+// A pseudo-random legal point that is not one of our own eyes. The same seed
+// on the same position always gives the same move.
+pub fn random_move(position: &Position, color: u8, seed: u64) -> Option<Point> {
+    let board = &position.board;
+    let mut candidates = Vec::new();
+    let mut empty = board.empty_points();
+    while let Some(index) = empty.first() {
+        empty.remove(index);
+        let (x, y) = board.point(index);
+        if !is_true_eye(board, Point::new(x, y), color) {
+            candidates.push(Point::new(x, y));
+        }
+    }
+    if candidates.is_empty() {
+        return None;
+    }
+
+    // Mixed so that nearby seeds pick unrelated points.
+    let start = (splitmix64(seed) % candidates.len() as u64) as usize;
+    (0..candidates.len())
+        .take(MAX_EVALUATIONS as usize)
+        .map(|offset| candidates[(start + offset) % candidates.len()])
+        .find(|point| position.is_legal(color, *point))
+}
+
+// Groups of `color` with a single liberty, largest first, together with
+// that liberty.
+fn groups_in_atari(board: &Board, color: u8) -> Vec<(u32, Point)> {
+    let mut groups = Vec::new();
+    let mut remaining = board.stones(color);
+    while let Some(index) = remaining.first() {
+        let (x, y) = board.point(index);
+        let group = board.group(x, y);
+        remaining = remaining.and_not(group);
+
+        let liberties = board.liberties(group);
+        if liberties.count() == 1 {
+            if let Some(liberty) = liberties.first() {
+                let (lx, ly) = board.point(liberty);
+                groups.push((group.count(), Point::new(lx, ly)));
+            }
+        }
+    }
+    groups.sort_by(|a, b| b.0.cmp(&a.0));
+    groups
+}
+
+// An empty point surrounded by `color` whose diagonals the opponent cannot
+// use to break it: at most one opposing diagonal in the centre and none on
+// the edge.
+pub fn is_true_eye(board: &Board, point: Point, color: u8) -> bool {
+    let Point { x, y } = point;
+    if board.get_stone_at_position(x, y) != 0 {
+        return false;
+    }
+
+    if !board.point_neighbours(x, y).and_not(board.stones(color)).is_empty() {
+        return false;
+    }
+
+    let opponent = if color == 1 { 2 } else { 1 };
+    let diagonals = [
+        (x.wrapping_sub(1), y.wrapping_sub(1)),
+        (x + 1, y.wrapping_sub(1)),
+        (x.wrapping_sub(1), y + 1),
+        (x + 1, y + 1),
+    ];
+    let mut on_board = 0;
+    let mut opposing = 0;
+    for (dx, dy) in diagonals {
+        if board.is_valid_position(dx, dy) {
+            on_board += 1;
+            if board.get_stone_at_position(dx, dy) == opponent {
+                opposing += 1;
+            }
+        }
+    }
+
+    if on_board == 4 { opposing <= 1 } else { opposing == 0 }
+}
+
+// Picks a move in order of priority: capture the largest group in atari,
+// rescue the largest own group in atari, then the first point from the
+// centre that neither fills an own eye nor puts the played stones in atari.
+// Returns None, meaning pass, when only own eyes are left.
+pub fn heuristic_move(position: &Position, color: u8) -> Option<Point> {
+    let board = &position.board;
+    let opponent = if color == 1 { 2 } else { 1 };
+    let mut budget = MAX_EVALUATIONS;
+
+    let mut best_capture: Option<(u32, Point)> = None;
+    for (_, liberty) in groups_in_atari(board, opponent) {
+        if budget == 0 {
+            break;
+        }
+        budget -= 1;
+        if let Ok((_, outcome)) = position.try_play(color, liberty) {
+            if best_capture.is_none_or(|(captured, _)| outcome.captured > captured) {
+                best_capture = Some((outcome.captured, liberty));
+            }
+        }
+    }
+    if let Some((_, point)) = best_capture {
+        return Some(point);
+    }
+
+    for (_, liberty) in groups_in_atari(board, color) {
+        if budget == 0 {
+            break;
+        }
+        budget -= 1;
+        if let Ok((after, _)) = position.try_play(color, liberty) {
+            if after.count_liberties(liberty.x, liberty.y) >= 2 {
+                return Some(liberty);
+            }
+        }
+    }
+
+    // Self-atari is only played when nothing better fits in the budget.
+    let mut fallback = None;
+    for point in spiral_order(board.size) {
+        if board.get_stone_at_position(point.x, point.y) != 0 || is_true_eye(board, point, color) {
+            continue;
+        }
+        if budget == 0 {
+            break;
+        }
+        budget -= 1;
+        if let Ok((after, outcome)) = position.try_play(color, point) {
+            if outcome.captured > 0 || after.count_liberties(point.x, point.y) >= 2 {
+                return Some(point);
+            }
+            fallback = fallback.or(Some(point));
+        }
+    }
+
+    fallback
+}
+
+// Area margin for `color` in whole points, less the largest group the
+// opponent could take straight away.
+fn evaluate(board: &Board, color: u8) -> i32 {
+    let score = board.score(RULES_AREA, [0, 0], 0);
+    let opponent = if color == 1 { 2 } else { 1 };
+    let (own, other) = (score.total(color), score.total(opponent));
+    let at_risk = groups_in_atari(board, color).first().map_or(0, |(size, _)| *size);
+    (own as i32 - other as i32) / 2 - at_risk as i32
+}
+
+// One ply of lookahead: every candidate is played out and the board scored,
+// counting the opponent's best immediate capture against it.
+pub fn search_move(position: &Position, color: u8) -> Option<Point> {
+    let board = &position.board;
+    let mut budget = SEARCH_CANDIDATES;
+    let mut best: Option<(i32, Point)> = None;
+
+    for point in spiral_order(board.size) {
+        if board.get_stone_at_position(point.x, point.y) != 0 || is_true_eye(board, point, color) {
+            continue;
+        }
+        if budget == 0 {
+            break;
+        }
+        budget -= 1;
+        if let Ok((after, _)) = position.try_play(color, point) {
+            let value = evaluate(&after, color);
+            if best.is_none_or(|(best_value, _)| value > best_value) {
+                best = Some((value, point));
+            }
+        }
+    }
+
+    best.map(|(_, point)| point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::KO_SIMPLE;
+
+    fn heuristic(rows: &[&str], color: u8) -> Option<Point> {
+        heuristic_move(&Position::new(Board::from_rows(7, rows), KO_SIMPLE, color), color)
+    }
+
+    #[test]
+    fn spiral_order_covers_every_point_once() {
+        for size in [7, 8, 9, 13, 19] {
+            let mut points = spiral_order(size);
+            assert_eq!(points[0], Point::new(size / 2, size / 2));
+            points.sort_by_key(|point| (point.y, point.x));
+            points.dedup();
+            assert_eq!(points.len(), size as usize * size as usize);
+        }
+    }
+
+    #[test]
+    fn captures_the_largest_group_in_atari() {
+        let rows = [
+            "X . . . . . .",
+            "O . . . O O .",
+            ". . . . X X O",
+            ". . . . O O .",
+        ];
+        // Colour 2 can take one stone at (1, 0) or two at (3, 2).
+        assert_eq!(heuristic(&rows, 2), Some(Point::new(3, 2)));
+    }
+
+    #[test]
+    fn extends_a_group_in_atari() {
+        let rows = [
+            ". . . . . . .",
+            ". . . X . . .",
+            ". . X O X . .",
+            ". . . . . . .",
+        ];
+        assert_eq!(heuristic(&rows, 2), Some(Point::new(3, 3)));
+    }
+
+    #[test]
+    fn avoids_self_atari() {
+        let rows = [
+            ". . . . . . .",
+            ". . . . . . .",
+            ". . . . . . .",
+            ". . O . O . .",
+            ". . . O . . .",
+        ];
+        // The centre would leave colour 1 with a single liberty.
+        assert_eq!(heuristic(&rows, 1), Some(Point::new(2, 2)));
+    }
+
+    #[test]
+    fn search_prefers_the_bigger_capture() {
+        let rows = [
+            "X . . . . . .",
+            "O . . . O O .",
+            ". . . . X X O",
+            ". . . . O O .",
+        ];
+        let position = Position::new(Board::from_rows(7, &rows), KO_SIMPLE, 2);
+        assert_eq!(search_move(&position, 2), Some(Point::new(3, 2)));
+    }
+
+    #[test]
+    fn random_moves_are_legal_and_repeatable() {
+        let rows = [". O", "O ."];
+        let position = Position::new(Board::from_rows(7, &rows), KO_SIMPLE, 1);
+        for seed in 0..32 {
+            let point = random_move(&position, 1, seed).unwrap();
+            assert!(position.is_legal(1, point));
+            assert_eq!(random_move(&position, 1, seed), Some(point));
+        }
+    }
+
+    #[test]
+    fn does_not_fill_own_true_eyes() {
+        let rows = [
+            ". O . O X . X",
+            "O O O O X X X",
+            "O . O O X . X",
+            "O O O O X X X",
+            "X X X X X X X",
+            "X . X X . X .",
+            "X X X X X X X",
+        ];
+        let board = Board::from_rows(7, &rows);
+        assert!(is_true_eye(&board, Point::new(1, 2), 2));
+        assert!(is_true_eye(&board, Point::new(1, 5), 1));
+        assert_eq!(heuristic(&rows, 1), None);
+
+        let position = Position::new(board, KO_SIMPLE, 1);
+        assert_eq!(random_move(&position, 1, 7), None);
+        assert_eq!(search_move(&position, 1), None);
+    }
+}
@@ -1 +1,50 @@
-pub const BOARD_SIZE: usize = 7;
+pub const MAX_BOARD_SIZE: usize = 19;
+pub const MAX_POINTS: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+pub const SUPPORTED_BOARD_SIZES: [u8; 4] = [7, 9, 13, 19];
+
+// Two bits per point, so each uint256 word holds 128 points.
+pub const POINTS_PER_WORD: usize = 128;
+pub const BOARD_WORDS: usize = MAX_POINTS.div_ceil(POINTS_PER_WORD);
+
+// Black moves first; White receives komi.
+pub const BLACK: u8 = 1;
+pub const WHITE: u8 = 2;
+
+pub const RULES_AREA: u8 = 0;
+pub const RULES_TERRITORY: u8 = 1;
+
+pub const KO_SIMPLE: u8 = 0;
+pub const KO_POSITIONAL_SUPERKO: u8 = 1;
+pub const KO_SITUATIONAL_SUPERKO: u8 = 2;
+
+// Stored in the ko coordinates when no point is currently forbidden.
+pub const NO_KO: u8 = u8::MAX;
+
+// Komi of handicap games before any compensation for the stones, in half
+// points.
+pub const HANDICAP_KOMI: u32 = 1;
+
+// Strategies for the built-in bot, weakest first.
+pub const DIFFICULTY_RANDOM: u8 = 0;
+pub const DIFFICULTY_SPIRAL: u8 = 1;
+pub const DIFFICULTY_HEURISTIC: u8 = 2;
+pub const DIFFICULTY_SEARCH: u8 = 3;
+pub const DIFFICULTY_MONTE_CARLO: u8 = 4;
+
+// Stored as the difficulty of games between two players.
+pub const NO_DIFFICULTY: u8 = u8::MAX;
+
+// The Monte Carlo bot runs at most this many playouts per move. It also
+// stops once it has spent the game's search budget, or when going on would
+// leave less than the reserve for the rest of the transaction.
+pub const MONTE_CARLO_PLAYOUTS: u32 = 64;
+pub const DEFAULT_SEARCH_GAS: u64 = 5_000_000;
+
+// Storage slots written after the bot picks its move: the move itself and,
+// if it ends the game, the result, ratings, registry and leaderboard. The
+// tests measure up to 36; the rest covers captures across every board word
+// and a player moving between distant leaderboard buckets.
+pub const MONTE_CARLO_RESERVED_SLOTS: u64 = 64;
+// Every reserved slot priced as a fresh SSTORE, plus room for the logs and
+// for scoring and the rating updates.
+pub const MONTE_CARLO_GAS_RESERVE: u64 = MONTE_CARLO_RESERVED_SLOTS * 22_100 + 500_000;
@@ -0,0 +1,61 @@
+use stylus_sdk::{alloy_sol_types::sol, prelude::*};
+
+use crate::position::IllegalMove;
+
+sol! {
+    #![sol(all_derives)]
+
+    error NoActiveGame(uint256 gameId);
+    error GameAlreadyEnded(uint256 gameId);
+    error NotAPlayer(uint256 gameId, address account);
+    error NotYourTurn(uint256 gameId);
+    error InvalidPosition(uint8 x, uint8 y);
+    error PositionOccupied(uint8 x, uint8 y);
+    error KoViolation(uint8 x, uint8 y);
+    error SuicideMove(uint8 x, uint8 y);
+    error UnsupportedBoardSize(uint8 boardSize);
+    error UnknownRuleSet(uint8 ruleSet);
+    error UnknownKoRule(uint8 koRule);
+    error UnknownDifficulty(uint8 difficulty);
+    error UnknownColor(uint8 color);
+    error UnsupportedHandicap(uint8 handicap, uint8 boardSize);
+    error InvalidOpponent(address opponent);
+    error NoOpenChallenge(uint256 gameId);
+    error InvalidSetup();
+    error StonesWithoutLiberties(uint8 x, uint8 y);
+    error FeatureDisabled();
+}
+
+#[derive(SolidityError, Debug, PartialEq)]
+pub enum GoError {
+    NoActiveGame(NoActiveGame),
+    GameAlreadyEnded(GameAlreadyEnded),
+    NotAPlayer(NotAPlayer),
+    NotYourTurn(NotYourTurn),
+    InvalidPosition(InvalidPosition),
+    PositionOccupied(PositionOccupied),
+    KoViolation(KoViolation),
+    SuicideMove(SuicideMove),
+    UnsupportedBoardSize(UnsupportedBoardSize),
+    UnknownRuleSet(UnknownRuleSet),
+    UnknownKoRule(UnknownKoRule),
+    UnknownDifficulty(UnknownDifficulty),
+    UnknownColor(UnknownColor),
+    UnsupportedHandicap(UnsupportedHandicap),
+    InvalidOpponent(InvalidOpponent),
+    NoOpenChallenge(NoOpenChallenge),
+    InvalidSetup(InvalidSetup),
+    StonesWithoutLiberties(StonesWithoutLiberties),
+    FeatureDisabled(FeatureDisabled),
+}
+
+impl GoError {
+    pub(crate) fn illegal_move(illegal: IllegalMove, x: u8, y: u8) -> Self {
+        match illegal {
+            IllegalMove::InvalidPosition => GoError::InvalidPosition(InvalidPosition { x, y }),
+            IllegalMove::PositionOccupied => GoError::PositionOccupied(PositionOccupied { x, y }),
+            IllegalMove::KoViolation => GoError::KoViolation(KoViolation { x, y }),
+            IllegalMove::SuicideMove => GoError::SuicideMove(SuicideMove { x, y }),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::vec::Vec;
+use stylus_sdk::alloy_sol_types::{abi::token::WordToken, sol, SolEvent, TopicList};
+use stylus_sdk::stylus_core::Host;
+
+// The SDK only exports `#[public]` functions in the ABI, so the events are
+// declared once here and expanded both into `sol!` and into the Solidity
+// declarations printed by `print_events_abi`.
+macro_rules! go_events {
+    ($(event $name:ident($($params:tt)*);)*) => {
+        sol! {
+            $(event $name($($params)*);)*
+        }
+
+        #[cfg(feature = "export-abi")]
+        const EVENT_DECLARATIONS: &[&str] = &[
+            $(concat!("event ", stringify!($name), "(", stringify!($($params)*), ");"),)*
+        ];
+    };
+}
+
+go_events! {
+    event GameCreated(uint256 indexed gameId, address indexed player, address indexed opponent, uint8 boardSize, uint8 ruleSet, uint8 koRule, uint32 komi, uint8 difficulty, uint8 color, uint8 handicap);
+    event ChallengeAccepted(uint256 indexed gameId, address indexed opponent);
+    event ChallengeCancelled(uint256 indexed gameId, address indexed account);
+    event StonePlaced(uint256 indexed gameId, address indexed player, uint8 x, uint8 y, uint8 color, uint32 captured);
+    event Passed(uint256 indexed gameId, address indexed player, uint8 color);
+    event MonteCarloSearch(uint256 indexed gameId, uint64 seed, uint32 playouts);
+    event Resigned(uint256 indexed gameId, address indexed player, uint8 color);
+    event GameEnded(uint256 indexed gameId, uint8 winner, uint32 playerScore, uint32 opponentScore, uint8 difficulty);
+    event RatingUpdated(address indexed player, uint32 rating, uint32 deviation);
+    event BotRatingUpdated(uint8 indexed difficulty, uint32 rating);
+    event PlayerRegistered(address indexed player, uint32 index);
+}
+
+// Does what the SDK's `log` does, but skips its `unwrap`: the panic path it
+// pulls in costs more deployed code than all of the events together.
+pub(crate) fn emit<T: SolEvent>(vm: &dyn Host, event: T) {
+    let mut topics = [WordToken::default(); 4];
+    if event.encode_topics_raw(&mut topics).is_err() {
+        return;
+    }
+    let count = T::TopicList::COUNT;
+    let mut bytes = Vec::with_capacity(32 * count);
+    for topic in &topics[..count] {
+        bytes.extend_from_slice(topic.as_slice());
+    }
+    event.encode_data_to(&mut bytes);
+    vm.emit_log(&bytes, count);
+}
+
+#[cfg(feature = "export-abi")]
+pub fn print_events_abi() {
+    println!();
+    println!("interface IGoGameEvents {{");
+    for declaration in EVENT_DECLARATIONS {
+        // `stringify!` may wrap long parameter lists across lines.
+        let declaration = declaration.split_whitespace().collect::<Vec<_>>().join(" ");
+        println!("    {declaration}");
+    }
+    println!("}}");
+}
@@ -0,0 +1,222 @@
+use core::borrow::{Borrow, BorrowMut};
+
+use stylus_sdk::prelude::*;
+
+use crate::GoGame;
+// Not all used unless every feature is in the build.
+#[allow(unused_imports)]
+use {
+    alloc::vec::Vec,
+    crate::constants::BOARD_WORDS,
+    crate::errors::GoError,
+    stylus_sdk::alloy_primitives::{Address, U256},
+};
+
+// The entry points of the optional features in Cargo.toml. The SDK routes
+// every function of a `#[public]` impl whatever its `#[cfg]`, so each
+// feature has a facet, GoGame's storage under another type, with a router
+// of its own that GoGame inherits. Builds with none of a facet's features
+// get an empty one, leaving its selectors out of both the code and the ABI.
+// Facets only forward to GoGame, where unit tests call the entry points
+// directly.
+macro_rules! facet {
+    ($facet:ident, $($feature:literal),+) => {
+        #[repr(transparent)]
+        pub struct $facet(GoGame);
+
+        // Sound as the facet is `repr(transparent)` over GoGame.
+        impl Borrow<$facet> for GoGame {
+            fn borrow(&self) -> &$facet {
+                unsafe { &*(self as *const GoGame as *const $facet) }
+            }
+        }
+
+        impl BorrowMut<$facet> for GoGame {
+            fn borrow_mut(&mut self) -> &mut $facet {
+                unsafe { &mut *(self as *mut GoGame as *mut $facet) }
+            }
+        }
+
+        #[cfg(not(any(test, $(feature = $feature),+)))]
+        #[public]
+        impl $facet {}
+    };
+}
+
+facet!(BoardArray, "board-array");
+facet!(GameArchive, "game-archive");
+facet!(MoveHistory, "move-history");
+facet!(SetupBoard, "handicap", "setup-positions");
+facet!(SetupPositions, "setup-positions");
+facet!(Challenges, "challenges");
+facet!(Ratings, "ratings");
+facet!(Registry, "registry");
+facet!(MonteCarlo, "monte-carlo");
+facet!(ScoreView, "score-view");
+
+#[cfg(any(test, feature = "board-array"))]
+#[public]
+impl BoardArray {
+    pub fn get_board_as_array(&self, game_id: U256) -> Vec<Vec<u8>> {
+        self.0.get_board_as_array(game_id)
+    }
+}
+
+#[cfg(any(test, feature = "game-archive"))]
+#[public]
+impl GameArchive {
+    pub fn get_finished_games(&self, player: Address) -> Vec<U256> {
+        self.0.get_finished_games(player)
+    }
+
+    pub fn get_finished_game_count(&self, player: Address) -> u32 {
+        self.0.get_finished_game_count(player)
+    }
+
+    pub fn get_finished_games_page(&self, player: Address, from: u32, count: u32) -> Vec<U256> {
+        self.0.get_finished_games_page(player, from, count)
+    }
+}
+
+#[cfg(any(test, feature = "move-history"))]
+#[public]
+impl MoveHistory {
+    pub fn get_move_count(&self, game_id: U256) -> u32 {
+        self.0.get_move_count(game_id)
+    }
+
+    pub fn get_moves(&self, game_id: U256, from: u32, count: u32) -> Vec<(u8, u8, u8, u32, u64)> {
+        self.0.get_moves(game_id, from, count)
+    }
+}
+
+#[cfg(any(test, feature = "handicap", feature = "setup-positions"))]
+#[public]
+impl SetupBoard {
+    pub fn get_setup_board(&self, game_id: U256) -> [U256; BOARD_WORDS] {
+        self.0.get_setup_board(game_id)
+    }
+}
+
+#[cfg(any(test, feature = "setup-positions"))]
+#[public]
+impl SetupPositions {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_game_from_position(&mut self,
+                                     board_size: u8,
+                                     setup: [U256; BOARD_WORDS],
+                                     white_first: bool,
+                                     rule_set: u8,
+                                     ko_rule: u8,
+                                     komi: u32,
+                                     difficulty: u8,
+                                     color: u8) -> Result<U256, GoError> {
+        self.0.create_game_from_position(board_size, setup, white_first, rule_set, ko_rule, komi, difficulty, color)
+    }
+}
+
+#[cfg(any(test, feature = "challenges"))]
+#[public]
+impl Challenges {
+    pub fn create_challenge(&mut self,
+                            opponent: Address,
+                            board_size: u8,
+                            rule_set: u8,
+                            ko_rule: u8,
+                            komi: u32,
+                            color: u8) -> Result<U256, GoError> {
+        self.0.create_challenge(opponent, board_size, rule_set, ko_rule, komi, color)
+    }
+
+    pub fn accept_challenge(&mut self, game_id: U256) -> Result<(), GoError> {
+        self.0.accept_challenge(game_id)
+    }
+
+    pub fn cancel_challenge(&mut self, game_id: U256) -> Result<(), GoError> {
+        self.0.cancel_challenge(game_id)
+    }
+}
+
+#[cfg(any(test, feature = "ratings"))]
+#[public]
+impl Ratings {
+    pub fn get_player_rating(&self, player: Address) -> u32 {
+        self.0.get_player_rating(player)
+    }
+
+    pub fn get_player_glicko(&self, player: Address) -> (u32, u32, u32) {
+        self.0.get_player_glicko(player)
+    }
+
+    pub fn get_bot_rating(&self, difficulty: u8) -> u32 {
+        self.0.get_bot_rating(difficulty)
+    }
+
+    pub fn get_top_players(&self) -> Vec<(Address, u32)> {
+        self.0.get_top_players()
+    }
+
+    pub fn get_leaderboard(&self, offset: u32, count: u32) -> Vec<(Address, u32)> {
+        self.0.get_leaderboard(offset, count)
+    }
+
+    pub fn get_player_rank(&self, player: Address) -> u32 {
+        self.0.get_player_rank(player)
+    }
+}
+
+#[cfg(any(test, feature = "registry"))]
+#[public]
+impl Registry {
+    pub fn get_total_players(&self) -> u32 {
+        self.0.get_total_players()
+    }
+
+    pub fn is_registered(&self, player: Address) -> bool {
+        self.0.is_registered(player)
+    }
+
+    pub fn get_player_profile(&self, player: Address) -> (u32, u32, u32, u32, u64, u64) {
+        self.0.get_player_profile(player)
+    }
+
+    pub fn get_player_stats(&self, player: Address) -> (u32, u32, u32, u32) {
+        self.0.get_player_stats(player)
+    }
+
+    pub fn get_player_difficulty_record(&self, player: Address, difficulty: u8) -> (u32, u32, u32) {
+        self.0.get_player_difficulty_record(player, difficulty)
+    }
+
+    pub fn get_player_board_size_record(&self, player: Address, board_size: u8) -> (u32, u32, u32) {
+        self.0.get_player_board_size_record(player, board_size)
+    }
+
+    pub fn get_players(&self, offset: u32, count: u32) -> Vec<Address> {
+        self.0.get_players(offset, count)
+    }
+
+    pub fn migrate_players(&mut self, count: u32) -> u32 {
+        self.0.migrate_players(count)
+    }
+}
+
+#[cfg(any(test, feature = "monte-carlo"))]
+#[public]
+impl MonteCarlo {
+    pub fn get_search_gas(&self, game_id: U256) -> u64 {
+        self.0.get_search_gas(game_id)
+    }
+
+    pub fn set_search_gas(&mut self, game_id: U256, gas: u64) -> Result<(), GoError> {
+        self.0.set_search_gas(game_id, gas)
+    }
+}
+
+#[cfg(any(test, feature = "score-view"))]
+#[public]
+impl ScoreView {
+    pub fn get_score(&self, game_id: U256) -> (u8, u32, u32, u32, u32, u32, u32, u32, u32, u32) {
+        self.0.get_score(game_id)
+    }
+}
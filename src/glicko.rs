@@ -0,0 +1,309 @@
+use crate::rating::INITIAL_RATING;
+
+// Glicko-2 in fixed point. Every real number below is an i128 scaled by
+// ONE, which leaves room for products of two values in the low thousands.
+type Fixed = i128;
+
+const ONE: Fixed = 1_000_000_000_000;
+const LN_2: Fixed = 693_147_180_560;
+const PI_SQUARED: Fixed = 9_869_604_401_089;
+// 400 / ln 10, converting between the Glicko and Glicko-2 scales.
+const GLICKO_SCALE: Fixed = 173_717_792_761_301;
+// System constant constraining volatility changes.
+const TAU: Fixed = 500_000_000_000;
+const EPSILON: Fixed = 1_000_000;
+// Bounds the volatility search so an update has a fixed worst-case cost.
+const MAX_ITERATIONS: u32 = 30;
+// The variance of a result grows without bound as the expected score nears
+// 0 or 1. Capping it keeps the volatility search within i128 when a heavy
+// favourite loses; it only binds for rating gaps of about 2400 or more.
+const MAX_VARIANCE: Fixed = 1_000_000;
+
+// Stored deviations are in thousandths of a rating point and volatilities
+// in millionths.
+pub const INITIAL_DEVIATION: u32 = 350_000;
+pub const MAX_DEVIATION: u32 = 350_000;
+pub const INITIAL_VOLATILITY: u32 = 60_000;
+
+// Inactivity is counted in whole rating periods of this many seconds.
+pub const RATING_PERIOD: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glicko {
+    pub rating: u32,
+    pub deviation: u32,
+    pub volatility: u32,
+}
+
+impl Default for Glicko {
+    fn default() -> Self {
+        Glicko {
+            rating: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            volatility: INITIAL_VOLATILITY,
+        }
+    }
+}
+
+impl Glicko {
+    // Rating less twice the deviation: the player is very likely at least
+    // this strong.
+    pub fn conservative(&self) -> u32 {
+        self.rating.saturating_sub(self.deviation / 500)
+    }
+
+    // Deviation grows by the volatility for every rating period without a
+    // game, up to the deviation of a new player.
+    pub fn decayed(self, periods: u64) -> Self {
+        if periods == 0 {
+            return self;
+        }
+        let phi = self.phi();
+        let sigma = self.sigma();
+        let grown = sqrt(mul(phi, phi) + mul(sigma, sigma) * periods.min(10_000) as Fixed);
+        Glicko {
+            deviation: to_deviation(grown).min(MAX_DEVIATION),
+            ..self
+        }
+    }
+
+    // One rating period with the given results, each an opponent and a score
+    // in thousandths (1000 win, 500 draw, 0 loss).
+    pub fn update(self, results: &[(Glicko, u32)]) -> Self {
+        if results.is_empty() {
+            return self.decayed(1);
+        }
+        let mu = self.mu();
+        let phi = self.phi();
+        let sigma = self.sigma();
+
+        let mut inverse_variance = 0;
+        let mut improvement = 0;
+        for (opponent, score) in results {
+            let g = g(opponent.phi());
+            let expected = expected(mu, opponent.mu(), g);
+            inverse_variance += mul(mul(g, g), mul(expected, ONE - expected));
+            improvement += mul(g, *score as Fixed * ONE / 1000 - expected);
+        }
+        let variance = div(ONE, inverse_variance.max(ONE / MAX_VARIANCE));
+        let delta = mul(variance, improvement);
+
+        let sigma = new_volatility(phi, sigma, variance, delta);
+        let phi_star_squared = mul(phi, phi) + mul(sigma, sigma);
+        let phi = div(ONE, sqrt(div(ONE, phi_star_squared) + inverse_variance));
+        let mu = mu + mul(mul(phi, phi), improvement);
+
+        Glicko {
+            rating: to_rating(mu),
+            deviation: to_deviation(phi).min(MAX_DEVIATION),
+            volatility: (sigma * 1_000_000 / ONE).max(1) as u32,
+        }
+    }
+
+    fn mu(&self) -> Fixed {
+        div((self.rating as Fixed - INITIAL_RATING as Fixed) * ONE, GLICKO_SCALE)
+    }
+
+    fn phi(&self) -> Fixed {
+        div(self.deviation as Fixed * ONE / 1000, GLICKO_SCALE)
+    }
+
+    fn sigma(&self) -> Fixed {
+        self.volatility as Fixed * ONE / 1_000_000
+    }
+}
+
+fn to_rating(mu: Fixed) -> u32 {
+    let rating = INITIAL_RATING as Fixed * ONE + mul(mu, GLICKO_SCALE);
+    // Stored ratings of zero read back as unrated.
+    ((rating + ONE / 2) / ONE).max(1) as u32
+}
+
+fn to_deviation(phi: Fixed) -> u32 {
+    ((mul(phi, GLICKO_SCALE) * 1000 + ONE / 2) / ONE) as u32
+}
+
+fn g(phi: Fixed) -> Fixed {
+    div(ONE, sqrt(ONE + div(3 * mul(phi, phi), PI_SQUARED)))
+}
+
+fn expected(mu: Fixed, opponent_mu: Fixed, g: Fixed) -> Fixed {
+    div(ONE, ONE + exp(-mul(g, mu - opponent_mu)))
+}
+
+// Step 5 of Glickman's description, using the Illinois variant of regula
+// falsi on f(x) = 0 where x = ln(sigma'^2).
+fn new_volatility(phi: Fixed, sigma: Fixed, variance: Fixed, delta: Fixed) -> Fixed {
+    let a = ln(mul(sigma, sigma));
+    let phi_squared = mul(phi, phi);
+    let delta_squared = mul(delta, delta);
+    let tau_squared = mul(TAU, TAU);
+    let f = |x: Fixed| {
+        let ex = exp(x);
+        let denominator = phi_squared + variance + ex;
+        // Dividing before multiplying keeps the terms in range; ex is at
+        // most the denominator.
+        mul(div(ex, denominator), div(delta_squared - phi_squared - variance - ex, denominator)) / 2
+            - div(x - a, tau_squared)
+    };
+
+    let mut upper_a = a;
+    let mut upper_b = if delta_squared > phi_squared + variance {
+        ln(delta_squared - phi_squared - variance)
+    } else {
+        let mut k = 1;
+        while k < MAX_ITERATIONS as Fixed && f(a - k * TAU) < 0 {
+            k += 1;
+        }
+        a - k * TAU
+    };
+    let mut f_a = f(upper_a);
+    let mut f_b = f(upper_b);
+
+    let mut iterations = 0;
+    while (upper_b - upper_a).abs() > EPSILON && f_b != f_a && iterations < MAX_ITERATIONS {
+        let c = upper_a + div(mul(upper_a - upper_b, f_a), f_b - f_a);
+        let f_c = f(c);
+        if (f_c < 0) != (f_b < 0) || f_c == 0 {
+            upper_a = upper_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2;
+        }
+        upper_b = c;
+        f_b = f_c;
+        iterations += 1;
+    }
+
+    exp(upper_a / 2)
+}
+
+fn mul(a: Fixed, b: Fixed) -> Fixed {
+    a * b / ONE
+}
+
+fn div(a: Fixed, b: Fixed) -> Fixed {
+    a * ONE / b
+}
+
+fn sqrt(x: Fixed) -> Fixed {
+    if x <= 0 {
+        return 0;
+    }
+    let target = x as u128 * ONE as u128;
+    let mut root = target;
+    let mut next = (root + 1) / 2;
+    while next < root {
+        root = next;
+        next = (root + target / root) / 2;
+    }
+    root as Fixed
+}
+
+// e^x = 2^k * e^r with |r| <= ln 2 / 2, then a Taylor series for e^r.
+fn exp(x: Fixed) -> Fixed {
+    let x = x.clamp(-40 * ONE, 40 * ONE);
+    let k = (x + x.signum() * LN_2 / 2) / LN_2;
+    let r = x - k * LN_2;
+
+    let mut sum = ONE;
+    let mut term = ONE;
+    for n in 1..30 {
+        term = mul(term, r) / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    if k >= 0 { sum << k } else { sum >> -k }
+}
+
+// ln x = k ln 2 + ln m with m in [1, 2), and ln m = 2 atanh((m - 1) / (m + 1)).
+fn ln(x: Fixed) -> Fixed {
+    let mut m = x.max(1);
+    let mut k = 0;
+    while m >= 2 * ONE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < ONE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    let z = div(m - ONE, m + ONE);
+    let z_squared = mul(z, z);
+    let mut sum = 0;
+    let mut term = z;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = mul(term, z_squared);
+        n += 2;
+    }
+
+    2 * sum + k * LN_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_point_functions_are_accurate() {
+        assert!((exp(ONE) - 2_718_281_828_459).abs() < 1_000);
+        assert!((exp(-3 * ONE) - 49_787_068_368).abs() < 1_000);
+        assert!((ln(10 * ONE) - 2_302_585_092_994).abs() < 1_000);
+        assert!((ln(ONE / 8) + 3 * LN_2).abs() < 1_000);
+        assert_eq!(sqrt(2 * ONE), 1_414_213_562_373);
+    }
+
+    // The worked example from Glickman's "Example of the Glicko-2 system".
+    #[test]
+    fn matches_the_published_example() {
+        let player = Glicko { rating: 1500, deviation: 200_000, volatility: 60_000 };
+        let opponents = [
+            (Glicko { rating: 1400, deviation: 30_000, volatility: 60_000 }, 1000),
+            (Glicko { rating: 1550, deviation: 100_000, volatility: 60_000 }, 0),
+            (Glicko { rating: 1700, deviation: 300_000, volatility: 60_000 }, 0),
+        ];
+        let updated = player.update(&opponents);
+        assert_eq!(updated.rating, 1464);
+        assert!(updated.deviation.abs_diff(151_520) < 20, "{updated:?}");
+        assert!(updated.volatility.abs_diff(59_996) <= 1, "{updated:?}");
+    }
+
+    #[test]
+    fn inactivity_widens_the_deviation() {
+        let player = Glicko { rating: 1800, deviation: 50_000, volatility: 60_000 };
+        assert_eq!(player.decayed(0), player);
+        let rested = player.decayed(10);
+        assert!(rested.deviation > 50_000 && rested.rating == 1800);
+        assert_eq!(player.decayed(1_000_000).deviation, MAX_DEVIATION);
+        assert_eq!(Glicko::default().conservative(), 800);
+    }
+
+    #[test]
+    fn ratings_never_fall_to_the_unrated_zero() {
+        let player = Glicko { rating: 5, deviation: 350_000, volatility: 60_000 };
+        let opponent = Glicko { rating: 5, deviation: 30_000, volatility: 60_000 };
+        assert_eq!(player.update(&[(opponent, 0)]).rating, 1);
+    }
+
+    // A heavy favourite losing leaves the result with a huge variance. The
+    // expected ratings come from a floating-point Glicko-2.
+    #[test]
+    fn large_rating_gaps_stay_in_range() {
+        let cases = [(1900, 400, 1885, 421), (2200, 700, 2185, 721), (2500, 1000, 2485, 1021), (5000, 100, 4985, 121)];
+        for (favourite, underdog, after_loss, after_upset) in cases {
+            let favourite = Glicko { rating: favourite, deviation: 50_000, volatility: 60_000 };
+            let underdog = Glicko { rating: underdog, deviation: 60_000, volatility: 60_000 };
+            let lost = favourite.update(&[(underdog, 0)]);
+            assert_eq!(lost.rating, after_loss);
+            assert!(lost.deviation.abs_diff(51_075) < 20 && lost.volatility.abs_diff(60_013) <= 1, "{lost:?}");
+            assert_eq!(underdog.update(&[(favourite, 1000)]).rating, after_upset);
+            assert_eq!(favourite.update(&[(underdog, 1000)]).rating, favourite.rating);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+
+use stylus_sdk::alloy_primitives::{Address, Uint};
+
+use crate::GoGame;
+
+// Scores above this share the top bucket.
+pub const MAX_LEADERBOARD_SCORE: u32 = 4095;
+
+// One Fenwick tree position per score. A power of two, so `tree_find` can
+// descend it bit by bit.
+const TREE_SIZE: u32 = MAX_LEADERBOARD_SCORE + 1;
+
+// Better scores come first in the tree, so the prefix sum up to a position
+// counts the players ranked at or above that score.
+fn position(score: u32) -> u32 {
+    TREE_SIZE - score.min(MAX_LEADERBOARD_SCORE)
+}
+
+fn score_at(position: u32) -> u32 {
+    TREE_SIZE - position
+}
+
+// Players are kept in one bucket per score, and a Fenwick tree counts the
+// players in each bucket. Moving a player, finding their rank and finding
+// the n-th player each touch O(log MAX_LEADERBOARD_SCORE) slots, however
+// many players there are.
+impl GoGame {
+    pub(crate) fn leaderboard_set(&mut self, player: Address, score: u32) {
+        let score = score.min(MAX_LEADERBOARD_SCORE);
+        if self.leaderboard_slot.get(player) != Uint::<32, 1>::ZERO {
+            let old_score = self.leaderboard_score.get(player).to::<u32>();
+            if old_score == score {
+                return;
+            }
+            self.leaderboard_remove(player, old_score);
+        } else {
+            let size = self.leaderboard_size.get();
+            self.leaderboard_size.set(size + Uint::<32, 1>::from(1u32));
+        }
+
+        let mut bucket = self.leaderboard_buckets.setter(Uint::<32, 1>::from(score));
+        bucket.push(player);
+        let slot = Uint::<32, 1>::from(bucket.len() as u32);
+        self.leaderboard_slot.insert(player, slot);
+        self.leaderboard_score.insert(player, Uint::<32, 1>::from(score));
+        self.tree_add(position(score), true);
+    }
+
+    // Swap-removes the player from their bucket, as `archive_game` does for
+    // active games.
+    fn leaderboard_remove(&mut self, player: Address, score: u32) {
+        let slot = self.leaderboard_slot.get(player);
+        let index = slot.to::<usize>() - 1;
+        let mut bucket = self.leaderboard_buckets.setter(Uint::<32, 1>::from(score));
+        let last = bucket.get(bucket.len() - 1).unwrap_or_default();
+        if let Some(mut moved) = bucket.setter(index) {
+            moved.set(last);
+        }
+        bucket.erase_last();
+
+        self.leaderboard_slot.insert(last, slot);
+        self.leaderboard_slot.delete(player);
+        self.tree_add(position(score), false);
+    }
+
+    // 1 plus the number of players with a better score; 0 when absent.
+    pub(crate) fn leaderboard_rank(&self, player: Address) -> u32 {
+        if self.leaderboard_slot.get(player) == Uint::<32, 1>::ZERO {
+            return 0;
+        }
+        let score = self.leaderboard_score.get(player).to::<u32>();
+        1 + self.tree_prefix(position(score) - 1)
+    }
+
+    // Players ranked offset + 1 to offset + count with their scores. Players
+    // on equal scores are listed in bucket order.
+    pub(crate) fn leaderboard_page(&self, offset: u32, count: u32) -> Vec<(Address, u32)> {
+        let size = self.leaderboard_size.get().to::<u32>();
+        let end = offset.saturating_add(count).min(size);
+        let mut page = Vec::with_capacity(end.saturating_sub(offset) as usize);
+
+        let mut next = offset + 1;
+        while next <= end {
+            let position = self.tree_find(next);
+            let score = score_at(position);
+            let ahead = self.tree_prefix(position - 1);
+            let bucket = self.leaderboard_buckets.get(Uint::<32, 1>::from(score));
+            let mut index = (next - 1 - ahead) as usize;
+            while index < bucket.len() && next <= end {
+                page.push((bucket.get(index).unwrap_or_default(), score));
+                index += 1;
+                next += 1;
+            }
+        }
+        page
+    }
+
+    fn tree_add(&mut self, position: u32, increment: bool) {
+        let mut i = position;
+        while i <= TREE_SIZE {
+            let key = Uint::<32, 1>::from(i);
+            let count = self.leaderboard_tree.get(key);
+            let one = Uint::<32, 1>::from(1u32);
+            self.leaderboard_tree.insert(key, if increment { count + one } else { count - one });
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn tree_prefix(&self, position: u32) -> u32 {
+        let mut sum = 0;
+        let mut i = position;
+        while i > 0 {
+            sum += self.leaderboard_tree.get(Uint::<32, 1>::from(i)).to::<u32>();
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    // Smallest position whose prefix sum reaches `k`, for 1 <= k <= size.
+    fn tree_find(&self, k: u32) -> u32 {
+        let mut position = 0;
+        let mut remaining = k;
+        let mut step = TREE_SIZE;
+        while step > 0 {
+            if position + step <= TREE_SIZE {
+                let count = self.leaderboard_tree.get(Uint::<32, 1>::from(position + step)).to::<u32>();
+                if count < remaining {
+                    position += step;
+                    remaining -= count;
+                }
+            }
+            step >>= 1;
+        }
+        position + 1
+    }
+}
@@ -3,667 +3,2049 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use stylus_sdk::{
-    alloy_primitives::{Address, Uint},
+    alloy_primitives::{Address, Uint, U256, U32, U8},
     prelude::*,
+    storage::{StorageAddress, StorageArray, StorageMap, StorageU256, StorageU32, StorageU64, StorageVec},
 };
 
-mod constants;
-use crate::constants::BOARD_SIZE;
+pub mod board;
+pub mod bot;
+pub mod constants;
+mod errors;
+mod events;
+mod facets;
+pub mod glicko;
+#[cfg(any(test, feature = "ratings"))]
+mod leaderboard;
+pub mod montecarlo;
+pub mod position;
+pub mod rating;
+#[cfg(any(test, feature = "registry"))]
+mod registry;
+mod reserved;
+pub mod scoring;
+pub mod sgf;
+pub mod zobrist;
+use crate::board::Board;
+use crate::errors::*;
+use crate::facets::{BoardArray, Challenges, GameArchive, MonteCarlo, MoveHistory, Ratings, Registry, ScoreView, SetupBoard, SetupPositions};
+#[cfg(feature = "export-abi")]
+pub use crate::events::print_events_abi;
+use crate::position::{History, MoveOutcome, Point, Position};
+use crate::reserved::Reserved;
+#[cfg(any(test, feature = "ratings"))]
+use crate::glicko::{Glicko, INITIAL_DEVIATION, INITIAL_VOLATILITY, RATING_PERIOD};
+use crate::rating::{DRAW, LOSS, WIN};
+#[cfg(any(test, feature = "ratings"))]
+use crate::rating::{BOT_DEVIATION, BOT_RATINGS, HANDICAP_STONE_RATING, INITIAL_RATING};
+use crate::constants::{
+    BLACK, BOARD_WORDS, DEFAULT_SEARCH_GAS, DIFFICULTY_HEURISTIC, DIFFICULTY_MONTE_CARLO, DIFFICULTY_RANDOM,
+    DIFFICULTY_SEARCH, DIFFICULTY_SPIRAL, HANDICAP_KOMI, KO_POSITIONAL_SUPERKO, KO_SIMPLE, KO_SITUATIONAL_SUPERKO,
+    MONTE_CARLO_GAS_RESERVE, MONTE_CARLO_PLAYOUTS, NO_DIFFICULTY, NO_KO, RULES_AREA, RULES_TERRITORY,
+    SUPPORTED_BOARD_SIZES, WHITE,
+};
+
+// Optional parts of the contract, see the features in Cargo.toml. Unit tests
+// build all of them.
+const GAME_ARCHIVE: bool = cfg!(any(test, feature = "game-archive"));
+const MOVE_HISTORY: bool = cfg!(any(test, feature = "move-history"));
+const HANDICAP: bool = cfg!(any(test, feature = "handicap"));
+const SETUP_STONES: bool = cfg!(any(test, feature = "handicap", feature = "setup-positions"));
+const SUPERKO: bool = cfg!(any(test, feature = "superko"));
+const RANDOM_BOT: bool = cfg!(any(test, feature = "random-bot"));
+const STRONG_BOTS: bool = cfg!(any(test, feature = "strong-bots"));
+const MONTE_CARLO: bool = cfg!(any(test, feature = "monte-carlo"));
+
+// Whether this build has the bot for a difficulty. The spiral bot, the one
+// the original contract played, is in every build.
+fn has_bot(difficulty: u8) -> bool {
+    match difficulty {
+        DIFFICULTY_RANDOM => RANDOM_BOT,
+        DIFFICULTY_SPIRAL => true,
+        DIFFICULTY_HEURISTIC | DIFFICULTY_SEARCH => STRONG_BOTS,
+        DIFFICULTY_MONTE_CARLO => MONTE_CARLO,
+        _ => false,
+    }
+}
+
+// Storage of the ratings and the registry, reserved in builds without them.
+#[cfg(any(test, feature = "ratings"))]
+type RatingStorage<T> = T;
+#[cfg(not(any(test, feature = "ratings")))]
+type RatingStorage<T> = Reserved<T>;
+#[cfg(any(test, feature = "registry"))]
+type RegistryStorage<T> = T;
+#[cfg(not(any(test, feature = "registry")))]
+type RegistryStorage<T> = Reserved<T>;
 
 sol_storage! {
+    // Passes are stored with NO_KO in both coordinates.
+    pub struct Move {
+        uint8 color;
+        uint8 x;
+        uint8 y;
+        uint32 captured;
+        uint64 board_hash;
+    }
+    
+    // Colours are BLACK (1, moves first) and WHITE (2); `player_color` says
+    // which one the creator took. The opponent is the zero address when
+    // playing against the contract. Captures and passes are kept per colour,
+    // `winner` and `resigned_by` per seat: 1 for the creator, 2 for the
+    // opponent.
+    pub struct Game {
+        address player;
+        address opponent;
+        bool accepted;
+        bool ended;
+        
+        uint8 board_size;
+        uint8 rule_set;
+        uint8 ko_rule;
+        uint32 komi;
+        uint8 difficulty;
+        // Colour of the creator; the opponent or bot plays the other one.
+        uint8 player_color;
+        // Black stones placed before the first move; White then opens.
+        uint8 handicap;
+        // Gas the Monte Carlo bot may spend per move; zero for the default.
+        uint64 search_gas;
+        
+        uint256[3] board;
+        // Written only in builds with handicap or setup-positions.
+        uint256[3] setup_board;
+        uint32 black_captures;
+        uint32 white_captures;
+        uint8 ko_x;
+        uint8 ko_y;
+        // Keys of the positions reached, kept only under superko.
+        mapping(uint64 => bool) seen_positions;
+        // Recorded only in builds with move-history.
+        Move[] moves;
+        
+        uint8 to_move;
+        bool black_passed;
+        bool white_passed;
+        
+        // Fixed when the game ends; resigned_by is the seat that resigned.
+        // A challenge cancelled before acceptance is ended but not accepted.
+        uint8 winner;
+        uint8 resigned_by;
+    }
+    
+    pub struct ResultCounts {
+        uint32 wins;
+        uint32 losses;
+        uint32 draws;
+    }
+    
+    // Results count finished games against people and the bot alike.
+    // Players migrated from the old registry start with no results, and
+    // zero timestamps until they next play.
+    pub struct PlayerProfile {
+        // Position in registered_players plus one; zero means unregistered.
+        uint32 index;
+        uint32 games_played;
+        uint32 wins;
+        uint32 losses;
+        uint32 draws;
+        uint64 first_played;
+        uint64 last_played;
+        
+        // Games against people are kept under NO_DIFFICULTY.
+        mapping(uint8 => ResultCounts) by_difficulty;
+        mapping(uint8 => ResultCounts) by_board_size;
+        // Stones the player captured, and moves by both sides.
+        uint32 captures;
+        uint64 total_moves;
+        uint32 win_streak;
+        uint32 longest_win_streak;
+    }
+
     #[entrypoint]
     pub struct GoGame {
-        mapping(address => uint128) game_boards;
-        mapping(address => uint32) white_captures;
-        mapping(address => uint32) black_captures;
-        mapping(address => uint8) last_move_x;
-        mapping(address => uint8) last_move_y;
-        
-        mapping(address => bool) player_passed;
-        mapping(address => bool) contract_passed;
-        mapping(address => bool) game_ended;
-        
+        // The first thirteen slots keep the layout of the original contract,
+        // which stored one game per address in eight mappings, so that its
+        // registry can still be migrated; see registry.rs. Points from
+        // before ratings were introduced are no longer awarded, and
+        // player_index and player_rank are not read.
+        Reserved<StorageArray<StorageU256, 8>> legacy_games;
         mapping(address => uint32) player_points;
-        mapping(uint32 => address) player_address_by_index;
-        mapping(address => uint32) player_index;
-        mapping(uint32 => uint32) player_rank;
+        RegistryStorage<StorageMap<U32, StorageAddress>> player_address_by_index;
+        Reserved<StorageMap<Address, StorageU32>> player_index;
+        Reserved<StorageMap<U32, StorageU32>> player_rank;
+        RegistryStorage<StorageU32> total_players;
+        
+        mapping(uint256 => Game) games;
+        uint256 game_count;
+        
+        // Slots hold the index into active_games plus one, so zero means absent.
+        mapping(address => uint256[]) active_games;
+        mapping(address => mapping(uint256 => uint256)) active_game_slot;
+        // Recorded only in builds with game-archive.
+        mapping(address => uint256[]) finished_games;
+        
+        // Zero means unrated, read as the initial rating.
+        RatingStorage<StorageMap<Address, StorageU32>> player_rating;
+        // Glicko-2 deviation in thousandths of a point, volatility in
+        // millionths, and the timestamp of the last rated game.
+        RatingStorage<StorageMap<Address, StorageU32>> player_deviation;
+        RatingStorage<StorageMap<Address, StorageU32>> player_volatility;
+        RatingStorage<StorageMap<Address, StorageU64>> player_last_rated;
+        RatingStorage<StorageMap<U8, StorageU32>> bot_rating;
 
-        uint32 total_players;
+        // Players bucketed by conservative rating as of their last rated
+        // game, with a Fenwick tree of bucket sizes; see leaderboard.rs.
+        RatingStorage<StorageMap<U32, StorageVec<StorageAddress>>> leaderboard_buckets;
+        // Index in the player's bucket plus one; zero when not listed.
+        RatingStorage<StorageMap<Address, StorageU32>> leaderboard_slot;
+        RatingStorage<StorageMap<Address, StorageU32>> leaderboard_score;
+        RatingStorage<StorageMap<U32, StorageU32>> leaderboard_tree;
+        RatingStorage<StorageU32> leaderboard_size;
+        
+        RegistryStorage<StorageMap<Address, PlayerProfile>> player_profiles;
+        RegistryStorage<StorageVec<StorageAddress>> registered_players;
+        // Old registry indices walked so far by migrate_players.
+        RegistryStorage<StorageU32> legacy_players_migrated;
+    }
+}
+
+// Options picked by the creator, validated by `start_game`.
+struct GameSettings {
+    rule_set: u8,
+    ko_rule: u8,
+    komi: u32,
+    difficulty: u8,
+    color: u8,
+    handicap: u8,
+}
+
+impl History for Game {
+    fn contains(&self, key: u64) -> bool {
+        SUPERKO && self.seen_positions.get(Uint::<64, 1>::from(key))
+    }
+}
+
+// Boards are stored as uint256 words in the layout of `Board::to_words`.
+fn board_words(board: &Board) -> [U256; BOARD_WORDS] {
+    board.to_words().map(U256::from_limbs)
+}
+
+fn board_from_words(size: u8, words: [U256; BOARD_WORDS]) -> Board {
+    Board::from_words(size, words.map(|word| word.into_limbs()))
+}
+
+// Rejects setups a game could never reach: points with both bits set,
+// stones off the board and groups left without liberties.
+#[cfg(any(test, feature = "setup-positions"))]
+fn setup_board(size: u8, words: [U256; BOARD_WORDS]) -> Result<Board, GoError> {
+    if !SUPPORTED_BOARD_SIZES.contains(&size) {
+        return Err(GoError::UnsupportedBoardSize(UnsupportedBoardSize { boardSize: size }));
+    }
+    
+    let board = board_from_words(size, words);
+    let (black, white) = (board.stones(BLACK), board.stones(WHITE));
+    if black.count() + white.count() + board.empty_points().count() != size as u32 * size as u32 {
+        return Err(GoError::InvalidSetup(InvalidSetup {}));
+    }
+    
+    for index in black.or(white).indices() {
+        let (x, y) = board.point(index);
+        if board.count_liberties(x, y) == 0 {
+            return Err(GoError::StonesWithoutLiberties(StonesWithoutLiberties { x, y }));
+        }
+    }
+    Ok(board)
+}
+
+fn require(enabled: bool) -> Result<(), GoError> {
+    if enabled {
+        Ok(())
+    } else {
+        Err(GoError::FeatureDisabled(FeatureDisabled {}))
     }
 }
 
 #[public]
+#[inherit(BoardArray, GameArchive, MoveHistory, SetupBoard, SetupPositions, Challenges, Ratings, Registry, MonteCarlo, ScoreView)]
 impl GoGame {
-    pub fn create_game(&mut self) {
+    // Entry points take the settings flat so they map onto plain ABI arguments.
+    // `komi` is in half points, so 13 gives White 6.5; handicap games set
+    // their own, see `start_game`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_game(&mut self,
+                       board_size: u8,
+                       rule_set: u8,
+                       ko_rule: u8,
+                       komi: u32,
+                       difficulty: u8,
+                       color: u8,
+                       handicap: u8) -> Result<U256, GoError> {
         let player = self.vm().msg_sender();
-        
-        let special_board = 1u128 << 127; 
-        self.game_boards.insert(player, Uint::<128, 2>::from(special_board));
-        
-        self.white_captures.insert(player, Uint::<32, 1>::from(0u32));
-        self.black_captures.insert(player, Uint::<32, 1>::from(0u32));
-        self.last_move_x.insert(player, Uint::<8, 1>::from(0u8));
-        self.last_move_y.insert(player, Uint::<8, 1>::from(0u8));
-        self.player_passed.insert(player, false);
-        self.contract_passed.insert(player, false);
-        self.game_ended.insert(player, false);
-        
-        if self.player_points.get(player) == Uint::<32, 1>::from(0u32) {
-            self.player_points.insert(player, Uint::<32, 1>::from(0u32));
-            self.total_players.set(self.total_players.get() + Uint::<32, 1>::from(1u32));
-        }
+        let settings = GameSettings { rule_set, ko_rule, komi, difficulty, color, handicap };
+        self.start_game(player, Address::ZERO, Board::empty(board_size), BLACK, settings)
     }
     
-    pub fn has_game(&self, player: Address) -> bool {
-        self.game_boards.get(player) != Uint::<128, 2>::from(0u128) && 
-        !self.game_ended.get(player)
+    pub fn get_game_count(&self) -> U256 {
+        self.game_count.get()
     }
     
-    pub fn get_board(&self, player: Address) -> u128 {
-        let board = self.game_boards.get(player);
-        board.try_into().unwrap_or(0u128)
+    pub fn get_active_games(&self, player: Address) -> Vec<U256> {
+        let games = self.active_games.get(player);
+        (0..games.len()).map(|i| games.get(i).unwrap_or_default()).collect()
     }
     
-    pub fn get_board_as_array(&self, player: Address) -> Vec<Vec<u8>> {
-        let board: u128 = self.game_boards.get(player).try_into().unwrap_or(0u128);
-        let mut result = Vec::with_capacity(BOARD_SIZE);
-        
-        for y in 0..BOARD_SIZE {
-            let mut row = Vec::with_capacity(BOARD_SIZE);
-            for x in 0..BOARD_SIZE {
-                let stone = self.get_stone_at_position(board, x as u8, y as u8);
-                row.push(stone);
-            }
-            result.push(row);
-        }
-        
-        result
+    pub fn is_game_active(&self, game_id: U256) -> bool {
+        let game = self.games.get(game_id);
+        game.board_size.get() != Uint::<8, 1>::from(0u8) && 
+        game.accepted.get() &&
+        !game.ended.get()
     }
     
-    pub fn get_player_points(&self, player: Address) -> u32 {
-        self.player_points.get(player).try_into().unwrap_or(0)
+    pub fn get_game_players(&self, game_id: U256) -> (Address, Address) {
+        let game = self.games.get(game_id);
+        (game.player.get(), game.opponent.get())
     }
     
-    pub fn get_total_players(&self) -> u32 {
-        self.total_players.get().try_into().unwrap_or(0)
+    pub fn get_player_color(&self, game_id: U256, player: Address) -> u8 {
+        self.player_color(game_id, player)
     }
     
-    pub fn set_piece(&mut self, x: u8, y: u8) {
-        let player = self.vm().msg_sender();
-        
-        assert!(self.has_game(player), "No active game found");
-        assert!(!self.game_ended.get(player), "Game already ended");
-        assert!(self.is_valid_position(x, y), "Invalid position");
-        
-        let board = self.get_board(player);
-        
-        let stone = self.get_stone_at_position(board, x, y);
-        assert!(stone == 0, "Position is already occupied");
-        
-        assert!(!self.is_ko_violation(player, x, y), "Move violates Ko rule");
-        
-        assert!(!self.would_be_suicide(board, x, y, 1), "Move would be suicide");
-        
-        let mut updated_board = self.set_stone_at_position(board, x, y, 1);
-        
-        let (board_after_capture, captured_stones, ko_x, ko_y) = self.capture_surrounded_stones(updated_board, x, y, 1);
-        updated_board = board_after_capture;
-        
-        let white_captures: u32 = self.white_captures.get(player).try_into().unwrap_or(0) + captured_stones;
-        let black_captures: u32 = self.black_captures.get(player).try_into().unwrap_or(0);
-        
-        let last_x = ko_x;
-        let last_y = ko_y;
+    pub fn get_turn(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).to_move.get().try_into().unwrap_or(0)
+    }
+    
+    pub fn get_board_size(&self, game_id: U256) -> u8 {
+        self.load_board(game_id).size
+    }
+    
+    pub fn get_board(&self, game_id: U256) -> [U256; BOARD_WORDS] {
+        board_words(&self.load_board(game_id))
+    }
+    
+    pub fn get_player_points(&self, player: Address) -> u32 {
+        self.player_points.get(player).try_into().unwrap_or(0)
+    }
+    
+    pub fn set_piece(&mut self, game_id: U256, x: u8, y: u8) -> Result<(), GoError> {
+        let sender = self.vm().msg_sender();
+        let color = self.color_to_move(game_id, sender)?;
         
-        self.player_passed.insert(player, false);
+        let game = self.games.get(game_id);
+        let mut position = self.load_position(game_id).with_earlier(&*game);
+        let outcome = position.play(color, Point::new(x, y))
+            .map_err(|illegal| GoError::illegal_move(illegal, x, y))?;
         
-        self.update_game(player, updated_board, white_captures, black_captures, last_x, last_y);
+        self.apply_move(game_id, &position.without_earlier(), x, y, color, outcome);
+        self.finish_turn(game_id, color)
+    }
+    
+    pub fn pass_turn(&mut self, game_id: U256) -> Result<(), GoError> {
+        let sender = self.vm().msg_sender();
+        let color = self.color_to_move(game_id, sender)?;
         
-        self.make_contract_move(player);
+        self.apply_pass(game_id, color);
+        self.finish_turn(game_id, color)
     }
     
-    pub fn pass_turn(&mut self) {
-        let player = self.vm().msg_sender();
+    pub fn resign(&mut self, game_id: U256) -> Result<(), GoError> {
+        let sender = self.vm().msg_sender();
         
-        assert!(self.has_game(player), "No active game found");
-        assert!(!self.game_ended.get(player), "Game already ended");
+        if self.games.get(game_id).ended.get() {
+            return Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id }));
+        }
+        if !self.is_game_active(game_id) {
+            return Err(GoError::NoActiveGame(NoActiveGame { gameId: game_id }));
+        }
+        
+        let color = self.player_color(game_id, sender);
+        if color == 0 {
+            return Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: sender }));
+        }
         
-        self.player_passed.insert(player, true);
+        events::emit(self.vm(), events::Resigned { gameId: game_id, player: sender, color });
         
-        self.make_contract_move(player);
+        self.end_game(game_id, color)
     }
     
-    pub fn is_game_ended(&self, player: Address) -> bool {
-        self.game_ended.get(player)
+    pub fn is_game_ended(&self, game_id: U256) -> bool {
+        self.games.get(game_id).ended.get()
     }
     
-    pub fn get_game_result(&self, player: Address) -> (u32, u32, u8) {
-        let white_captures = self.white_captures.get(player).try_into().unwrap_or(0);
-        let black_captures = self.black_captures.get(player).try_into().unwrap_or(0);
-        
-        let winner = if white_captures > black_captures {
-            1u8 
-        } else if white_captures < black_captures {
-            2u8 
+    // Scores are (creator, opponent) in half points, and the winner is 1 for
+    // the creator, 2 for the opponent or bot and 0 for a draw, whichever
+    // colours they play.
+    pub fn get_game_result(&self, game_id: U256) -> (u32, u32, u8) {
+        let score = self.current_score(game_id);
+        let game = self.games.get(game_id);
+        let winner = if game.ended.get() {
+            game.winner.get().try_into().unwrap_or(0)
         } else {
-            0u8 
+            self.seat_of(game_id, score.winner())
         };
-        
-        (white_captures, black_captures, winner)
+        let (own, other) = self.seat_colors(game_id);
+        (score.total(own), score.total(other), winner)
+    }
+    
+    // Seat of the resigning side, as in `get_game_result`.
+    pub fn get_resigned_by(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).resigned_by.get().try_into().unwrap_or(0)
     }
 
-    pub fn get_top_players(&self) -> Vec<(Address, u32)> {
-        let max_players_to_fetch = 10u32;
-        let total_existing_players = self.total_players.get().try_into().unwrap_or(0);
-        let max_players = max_players_to_fetch.min(total_existing_players);
-        
-        let mut leaderboard = Vec::with_capacity(max_players as usize);
-        
-        let mut all_players = Vec::with_capacity(total_existing_players as usize);
-        
-        for i in 1..=total_existing_players {
-            let player_addr = self.player_address_by_index.get(Uint::<32, 1>::from(i));
-            if player_addr != Address::ZERO {
-                let points = self.player_points.get(player_addr).try_into().unwrap_or(0);
-                all_players.push((player_addr, points));
+    pub fn get_rule_set(&self, game_id: U256) -> u8 {
+        self.rule_set_of(game_id)
+    }
+
+    pub fn get_ko_rule(&self, game_id: U256) -> u8 {
+        self.ko_rule_of(game_id)
+    }
+
+    pub fn get_difficulty(&self, game_id: U256) -> u8 {
+        self.difficulty_of(game_id)
+    }
+
+    pub fn get_handicap(&self, game_id: U256) -> u8 {
+        self.handicap_of(game_id)
+    }
+}
+
+// Entry points of the optional features, routed by their facets; see
+// facets.rs.
+#[cfg(any(test, feature = "board-array"))]
+impl GoGame {
+    pub fn get_board_as_array(&self, game_id: U256) -> Vec<Vec<u8>> {
+        let board = self.load_board(game_id);
+        let size = board.size as usize;
+        let mut result = Vec::with_capacity(size);
+        
+        for y in 0..size {
+            let mut row = Vec::with_capacity(size);
+            for x in 0..size {
+                let stone = board.get_stone_at_position(x as u8, y as u8);
+                row.push(stone);
             }
+            result.push(row);
         }
         
-        all_players.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        for i in 0..max_players as usize {
-            if i < all_players.len() {
-                leaderboard.push(all_players[i]);
-            }
+        result
+    }
+}
+
+#[cfg(any(test, feature = "handicap", feature = "setup-positions"))]
+impl GoGame {
+    // Handicap or setup stones placed before the first move, as in `get_board`.
+    pub fn get_setup_board(&self, game_id: U256) -> [U256; BOARD_WORDS] {
+        let game = self.games.get(game_id);
+        let mut words = [U256::ZERO; BOARD_WORDS];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = game.setup_board.get(i).unwrap_or_default();
         }
-        
-        leaderboard
+        words
+    }
+}
+
+#[cfg(any(test, feature = "game-archive"))]
+impl GoGame {
+    pub fn get_finished_games(&self, player: Address) -> Vec<U256> {
+        let games = self.finished_games.get(player);
+        (0..games.len()).map(|i| games.get(i).unwrap_or_default()).collect()
     }
     
-    pub fn get_player_rank(&self, player: Address) -> u32 {
-        let player_points = self.player_points.get(player).try_into().unwrap_or(0);
-        if player_points == 0 {
-            return 0;
-        }
-        
-        let mut rank = 1;
-        
-        for i in 1..=self.total_players.get().try_into().unwrap_or(0) {
-            let other_player = self.player_address_by_index.get(Uint::<32, 1>::from(i));
-            if other_player != player && other_player != Address::ZERO {
-                let other_points = self.player_points.get(other_player).try_into().unwrap_or(0);
-                if other_points > player_points {
-                    rank += 1;
-                }
+    pub fn get_finished_game_count(&self, player: Address) -> u32 {
+        self.finished_games.get(player).len() as u32
+    }
+    
+    // Finished games in the order they ended.
+    pub fn get_finished_games_page(&self, player: Address, from: u32, count: u32) -> Vec<U256> {
+        let games = self.finished_games.get(player);
+        let total = games.len();
+        let start = (from as usize).min(total);
+        let end = start.saturating_add(count as usize).min(total);
+        (start..end).map(|i| games.get(i).unwrap_or_default()).collect()
+    }
+}
+
+#[cfg(any(test, feature = "move-history"))]
+impl GoGame {
+    pub fn get_move_count(&self, game_id: U256) -> u32 {
+        self.games.get(game_id).moves.len() as u32
+    }
+    
+    // Each move is (color, x, y, captured, board_hash).
+    pub fn get_moves(&self, game_id: U256, from: u32, count: u32) -> Vec<(u8, u8, u8, u32, u64)> {
+        let game = self.games.get(game_id);
+        let total = game.moves.len();
+        let start = (from as usize).min(total);
+        let end = start.saturating_add(count as usize).min(total);
+        
+        let mut result = Vec::with_capacity(end - start);
+        for i in start..end {
+            if let Some(entry) = game.moves.get(i) {
+                result.push((
+                    entry.color.get().try_into().unwrap_or(0),
+                    entry.x.get().try_into().unwrap_or(NO_KO),
+                    entry.y.get().try_into().unwrap_or(NO_KO),
+                    entry.captured.get().try_into().unwrap_or(0),
+                    entry.board_hash.get().to::<u64>(),
+                ));
             }
         }
+        result
+    }
+}
+
+#[cfg(any(test, feature = "setup-positions"))]
+impl GoGame {
+    // Starts from stones packed as in `get_board`, which `sgf::setup_words`
+    // builds from an SGF record off-chain. `white_first` gives White the
+    // first move, which the bot plays if the creator is Black.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_game_from_position(&mut self,
+                                     board_size: u8,
+                                     setup: [U256; BOARD_WORDS],
+                                     white_first: bool,
+                                     rule_set: u8,
+                                     ko_rule: u8,
+                                     komi: u32,
+                                     difficulty: u8,
+                                     color: u8) -> Result<U256, GoError> {
+        let player = self.vm().msg_sender();
+        let board = setup_board(board_size, setup)?;
         
-        rank
+        let first = if white_first { WHITE } else { BLACK };
+        let settings = GameSettings { rule_set, ko_rule, komi, difficulty, color, handicap: 0 };
+        self.start_game(player, Address::ZERO, board, first, settings)
     }
 }
 
+#[cfg(any(test, feature = "challenges"))]
 impl GoGame {
-    fn update_game(&mut self, 
-                  player: Address, 
-                  board: u128,
-                  white_captures: u32, 
-                  black_captures: u32, 
-                  last_move_x: u8, 
-                  last_move_y: u8) {
-        self.game_boards.insert(player, Uint::<128, 2>::from(board));
-        self.white_captures.insert(player, Uint::<32, 1>::from(white_captures));
-        self.black_captures.insert(player, Uint::<32, 1>::from(black_captures));
-        self.last_move_x.insert(player, Uint::<8, 1>::from(last_move_x));
-        self.last_move_y.insert(player, Uint::<8, 1>::from(last_move_y));
+    // `komi` is in half points, as in `create_game`.
+    pub fn create_challenge(&mut self,
+                            opponent: Address,
+                            board_size: u8,
+                            rule_set: u8,
+                            ko_rule: u8,
+                            komi: u32,
+                            color: u8) -> Result<U256, GoError> {
+        let player = self.vm().msg_sender();
+        if opponent == Address::ZERO || opponent == player {
+            return Err(GoError::InvalidOpponent(InvalidOpponent { opponent }));
+        }
+        let settings = GameSettings { rule_set, ko_rule, komi, difficulty: NO_DIFFICULTY, color, handicap: 0 };
+        self.start_game(player, opponent, Board::empty(board_size), BLACK, settings)
     }
     
-    fn get_stone_at_position(&self, board: u128, x: u8, y: u8) -> u8 {
-        let position = y as usize * BOARD_SIZE + x as usize;
-        let shift = position * 2;
+    pub fn accept_challenge(&mut self, game_id: U256) -> Result<(), GoError> {
+        let player = self.vm().msg_sender();
         
-        if shift >= 98 { 
-            return 0;
+        let mut game = self.games.setter(game_id);
+        if game.opponent.get() != player || game.accepted.get() || game.ended.get() {
+            return Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: game_id }));
         }
+        game.accepted.set(true);
+        
+        self.register_player(player);
         
-        let value = (board >> shift) & 0b11;
-        value as u8
+        events::emit(self.vm(), events::ChallengeAccepted { gameId: game_id, opponent: player });
+        Ok(())
     }
     
-    fn set_stone_at_position(&self, board: u128, x: u8, y: u8, stone: u8) -> u128 {
-        let position = y as usize * BOARD_SIZE + x as usize;
-        let shift = position * 2;
-        if shift >= 98 {
-             return board;
+    // The creator can withdraw a challenge and the opponent can decline it
+    // until it is accepted. It leaves both active lists without being
+    // recorded as a finished game.
+    pub fn cancel_challenge(&mut self, game_id: U256) -> Result<(), GoError> {
+        let sender = self.vm().msg_sender();
+        
+        let (player, opponent) = self.get_game_players(game_id);
+        if opponent == Address::ZERO || (sender != player && sender != opponent) {
+            return Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: sender }));
         }
-        let cleared_board = board & !(0b11u128 << shift);
-        cleared_board | ((stone as u128) << shift)
+        let mut game = self.games.setter(game_id);
+        if game.accepted.get() || game.ended.get() {
+            return Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: game_id }));
+        }
+        game.ended.set(true);
+        
+        self.remove_active_game(player, game_id);
+        self.remove_active_game(opponent, game_id);
+        
+        events::emit(self.vm(), events::ChallengeCancelled { gameId: game_id, account: sender });
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "ratings"))]
+impl GoGame {
+    pub fn get_player_rating(&self, player: Address) -> u32 {
+        self.rating_of(player)
+    }
+    
+    // (rating, deviation, volatility), with the deviation in thousandths of
+    // a point as of the current block and the volatility in millionths.
+    pub fn get_player_glicko(&self, player: Address) -> (u32, u32, u32) {
+        let glicko = self.glicko_of(player);
+        (glicko.rating, glicko.deviation, glicko.volatility)
+    }
+    
+    pub fn get_bot_rating(&self, difficulty: u8) -> u32 {
+        self.bot_rating_of(difficulty)
+    }
+    
+    // Players are ranked by their conservative rating, the rating less twice
+    // the deviation, as of their last rated game; that value is also returned.
+    pub fn get_top_players(&self) -> Vec<(Address, u32)> {
+        self.leaderboard_page(0, 10)
     }
     
-    fn is_valid_position(&self, x: u8, y: u8) -> bool {
-        x < BOARD_SIZE as u8 && y < BOARD_SIZE as u8
+    pub fn get_leaderboard(&self, offset: u32, count: u32) -> Vec<(Address, u32)> {
+        self.leaderboard_page(offset, count)
     }
     
-    fn count_liberties(&self, board: u128, x: u8, y: u8) -> u32 {
-        let stone_color = self.get_stone_at_position(board, x, y);
-        if stone_color == 0 {
-            return 0; 
+    // 0 for players who have not finished a rated game.
+    pub fn get_player_rank(&self, player: Address) -> u32 {
+        self.leaderboard_rank(player)
+    }
+}
+
+#[cfg(any(test, feature = "registry"))]
+impl GoGame {
+    pub fn get_total_players(&self) -> u32 {
+        self.registered_players.len() as u32
+    }
+    
+    pub fn is_registered(&self, player: Address) -> bool {
+        self.is_registered_player(player)
+    }
+    
+    // (games played, wins, losses, draws, first played, last played), with
+    // the dates as block timestamps.
+    pub fn get_player_profile(&self, player: Address) -> (u32, u32, u32, u32, u64, u64) {
+        let profile = self.player_profiles.get(player);
+        (
+            profile.games_played.get().try_into().unwrap_or(0),
+            profile.wins.get().try_into().unwrap_or(0),
+            profile.losses.get().try_into().unwrap_or(0),
+            profile.draws.get().try_into().unwrap_or(0),
+            profile.first_played.get().to::<u64>(),
+            profile.last_played.get().to::<u64>(),
+        )
+    }
+    
+    // (captures, longest win streak, current win streak, average game
+    // length in moves by both sides, rounded down).
+    pub fn get_player_stats(&self, player: Address) -> (u32, u32, u32, u32) {
+        let profile = self.player_profiles.get(player);
+        let games_played = profile.games_played.get().to::<u64>();
+        let average = profile.total_moves.get().to::<u64>().checked_div(games_played).unwrap_or(0);
+        (
+            profile.captures.get().try_into().unwrap_or(0),
+            profile.longest_win_streak.get().try_into().unwrap_or(0),
+            profile.win_streak.get().try_into().unwrap_or(0),
+            average as u32,
+        )
+    }
+    
+    // (wins, losses, draws) against the bot at `difficulty`, or against
+    // people for NO_DIFFICULTY.
+    pub fn get_player_difficulty_record(&self, player: Address, difficulty: u8) -> (u32, u32, u32) {
+        let profile = self.player_profiles.get(player);
+        Self::result_counts(&profile.by_difficulty.get(Uint::<8, 1>::from(difficulty)))
+    }
+    
+    pub fn get_player_board_size_record(&self, player: Address, board_size: u8) -> (u32, u32, u32) {
+        let profile = self.player_profiles.get(player);
+        Self::result_counts(&profile.by_board_size.get(Uint::<8, 1>::from(board_size)))
+    }
+    
+    // Players in registration order.
+    pub fn get_players(&self, offset: u32, count: u32) -> Vec<Address> {
+        self.registered_page(offset, count)
+    }
+    
+    // Anyone may call this, `count` old registry indices at a time, until
+    // it returns zero: the number of indices left to walk.
+    pub fn migrate_players(&mut self, count: u32) -> u32 {
+        self.migrate_legacy_players(count)
+    }
+}
+
+#[cfg(any(test, feature = "monte-carlo"))]
+impl GoGame {
+    pub fn get_search_gas(&self, game_id: U256) -> u64 {
+        self.search_gas_of(game_id)
+    }
+    
+    // The creator of a bot game pays for the bot's moves, so they choose how
+    // much gas the Monte Carlo search may spend on each; zero restores the
+    // default. The bot's opening move, if any, uses the default.
+    pub fn set_search_gas(&mut self, game_id: U256, gas: u64) -> Result<(), GoError> {
+        let sender = self.vm().msg_sender();
+        if self.games.get(game_id).ended.get() {
+            return Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id }));
+        }
+        if !self.is_game_active(game_id) || !self.is_bot_game(game_id) {
+            return Err(GoError::NoActiveGame(NoActiveGame { gameId: game_id }));
+        }
+        if self.games.get(game_id).player.get() != sender {
+            return Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: sender }));
         }
         
-        let mut visited: u128 = 0;
-        let mut liberty_set: u128 = 0;
+        self.games.setter(game_id).search_gas.set(Uint::<64, 1>::from(gas));
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "score-view"))]
+impl GoGame {
+    // (rule set, creator's territory, stones and prisoners, opponent's
+    // territory, stones and prisoners, komi, creator's total, opponent's
+    // total). Counts are in points; komi and the totals are in half points,
+    // as in `get_game_result`.
+    pub fn get_score(&self, game_id: U256) -> (u8, u32, u32, u32, u32, u32, u32, u32, u32, u32) {
+        let score = self.current_score(game_id);
+        let (own, other) = self.seat_colors(game_id);
+        let (own_index, other_index) = (own as usize - 1, other as usize - 1);
+        (
+            score.rule_set,
+            score.territory[own_index],
+            score.stones[own_index],
+            score.prisoners[own_index],
+            score.territory[other_index],
+            score.stones[other_index],
+            score.prisoners[other_index],
+            score.komi,
+            score.total(own),
+            score.total(other),
+        )
+    }
+}
+
+impl GoGame {
+    fn start_game(&mut self,
+                  player: Address,
+                  opponent: Address,
+                  setup: Board,
+                  first: u8,
+                  settings: GameSettings) -> Result<U256, GoError> {
+        let GameSettings { rule_set, ko_rule, mut komi, difficulty, color, handicap } = settings;
+        let board_size = setup.size;
+        if !SUPPORTED_BOARD_SIZES.contains(&board_size) {
+            return Err(GoError::UnsupportedBoardSize(UnsupportedBoardSize { boardSize: board_size }));
+        }
+        if rule_set != RULES_AREA && rule_set != RULES_TERRITORY {
+            return Err(GoError::UnknownRuleSet(UnknownRuleSet { ruleSet: rule_set }));
+        }
+        if ko_rule != KO_SIMPLE && ko_rule != KO_POSITIONAL_SUPERKO && ko_rule != KO_SITUATIONAL_SUPERKO {
+            return Err(GoError::UnknownKoRule(UnknownKoRule { koRule: ko_rule }));
+        }
+        if ko_rule != KO_SIMPLE {
+            require(SUPERKO)?;
+        }
+        if opponent == Address::ZERO && difficulty > DIFFICULTY_MONTE_CARLO {
+            return Err(GoError::UnknownDifficulty(UnknownDifficulty { difficulty }));
+        }
+        if opponent == Address::ZERO {
+            require(has_bot(difficulty))?;
+        }
+        if color != BLACK && color != WHITE {
+            return Err(GoError::UnknownColor(UnknownColor { color }));
+        }
         
-        let mut stack = Vec::with_capacity(BOARD_SIZE);
-        stack.push((x, y));
+        // Handicap games replace the requested komi with half a point, plus
+        // a point per handicap stone under area scoring, where those stones
+        // would otherwise count for Black.
+        let (setup, first) = if handicap == 0 {
+            (setup, first)
+        } else {
+            require(HANDICAP)?;
+            let board = Board::with_handicap(board_size, handicap)
+                .ok_or(GoError::UnsupportedHandicap(UnsupportedHandicap { handicap, boardSize: board_size }))?;
+            komi = HANDICAP_KOMI + if rule_set == RULES_AREA { 2 * handicap as u32 } else { 0 };
+            (board, WHITE)
+        };
         
-        while let Some((curr_x, curr_y)) = stack.pop() {
-            let pos_idx = curr_y as usize * BOARD_SIZE + curr_x as usize;
-            let pos_bit = 1u128 << pos_idx;
-
-            if visited & pos_bit != 0 {
-                continue;
-            }
-            
-            visited |= pos_bit;
-            
-            // Right
-            if curr_x + 1 < BOARD_SIZE as u8 {
-                let nx = curr_x + 1;
-                let neighbor_stone = self.get_stone_at_position(board, nx, curr_y);
-                let neighbor_idx = curr_y as usize * BOARD_SIZE + nx as usize;
-                let neighbor_bit = 1u128 << neighbor_idx;
-                
-                if neighbor_stone == 0 {
-                    liberty_set |= neighbor_bit;
-                } else if neighbor_stone == stone_color && (visited & neighbor_bit) == 0 {
-                    stack.push((nx, curr_y));
-                }
-            }
-            
-            // Down
-            if curr_y + 1 < BOARD_SIZE as u8 {
-                let ny = curr_y + 1;
-                let neighbor_stone = self.get_stone_at_position(board, curr_x, ny);
-                let neighbor_idx = ny as usize * BOARD_SIZE + curr_x as usize;
-                let neighbor_bit = 1u128 << neighbor_idx;
-                
-                if neighbor_stone == 0 {
-                    liberty_set |= neighbor_bit;
-                } else if neighbor_stone == stone_color && (visited & neighbor_bit) == 0 {
-                    stack.push((curr_x, ny));
-                }
-            }
-            
-            // Left
-            if curr_x > 0 {
-                let nx = curr_x - 1;
-                let neighbor_stone = self.get_stone_at_position(board, nx, curr_y);
-                let neighbor_idx = curr_y as usize * BOARD_SIZE + nx as usize;
-                let neighbor_bit = 1u128 << neighbor_idx;
-                
-                if neighbor_stone == 0 {
-                    liberty_set |= neighbor_bit;
-                } else if neighbor_stone == stone_color && (visited & neighbor_bit) == 0 {
-                    stack.push((nx, curr_y));
-                }
-            }
-            
-            // Up
-            if curr_y > 0 {
-                let ny = curr_y - 1;
-                let neighbor_stone = self.get_stone_at_position(board, curr_x, ny);
-                let neighbor_idx = ny as usize * BOARD_SIZE + curr_x as usize;
-                let neighbor_bit = 1u128 << neighbor_idx;
-                
-                if neighbor_stone == 0 {
-                    liberty_set |= neighbor_bit;
-                } else if neighbor_stone == stone_color && (visited & neighbor_bit) == 0 {
-                    stack.push((curr_x, ny));
+        let game_id = self.game_count.get() + U256::from(1u8);
+        self.game_count.set(game_id);
+        
+        let mut game = self.games.setter(game_id);
+        game.player.set(player);
+        game.opponent.set(opponent);
+        game.accepted.set(opponent == Address::ZERO);
+        game.board_size.set(Uint::<8, 1>::from(board_size));
+        game.rule_set.set(Uint::<8, 1>::from(rule_set));
+        game.ko_rule.set(Uint::<8, 1>::from(ko_rule));
+        game.komi.set(Uint::<32, 1>::from(komi));
+        game.difficulty.set(Uint::<8, 1>::from(difficulty));
+        game.player_color.set(Uint::<8, 1>::from(color));
+        game.handicap.set(Uint::<8, 1>::from(handicap));
+        game.ko_x.set(Uint::<8, 1>::from(NO_KO));
+        game.ko_y.set(Uint::<8, 1>::from(NO_KO));
+        // Bot games keep the creator's colour here, as the bot replies within
+        // the same call.
+        let bot_opens = opponent == Address::ZERO && first != color;
+        game.to_move.set(Uint::<8, 1>::from(if opponent == Address::ZERO { color } else { first }));
+        
+        if SETUP_STONES && setup != Board::empty(board_size) {
+            let mut game = self.games.setter(game_id);
+            for (i, word) in board_words(&setup).iter().enumerate() {
+                if let Some(mut slot) = game.setup_board.setter(i) {
+                    slot.set(*word);
                 }
             }
         }
         
-        liberty_set.count_ones()
+        self.store_position(game_id, &Position::new(setup, ko_rule, first));
+        
+        self.add_active_game(player, game_id);
+        if opponent != Address::ZERO {
+            self.add_active_game(opponent, game_id);
+        }
+        
+        self.register_player(player);
+        
+        events::emit(self.vm(), events::GameCreated {
+            gameId: game_id,
+            player,
+            opponent,
+            boardSize: board_size,
+            ruleSet: rule_set,
+            koRule: ko_rule,
+            komi,
+            difficulty,
+            color,
+            handicap,
+        });
+        
+        if bot_opens {
+            self.make_contract_move(game_id)?;
+        }
+        Ok(game_id)
     }
     
-    fn make_contract_move(&mut self, player: Address) {
-        let board = self.get_board(player);
-        
-        let mut found_move = false;
-        let mut contract_x = 0u8;
-        let mut contract_y = 0u8;
+    // Returns the colour of `sender` if it is their move in an active game.
+    fn color_to_move(&self, game_id: U256, sender: Address) -> Result<u8, GoError> {
+        if self.games.get(game_id).ended.get() {
+            return Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id }));
+        }
+        if !self.is_game_active(game_id) {
+            return Err(GoError::NoActiveGame(NoActiveGame { gameId: game_id }));
+        }
         
-        let center = BOARD_SIZE as u8 / 2;
+        let color = self.player_color(game_id, sender);
+        if color == 0 {
+            return Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: sender }));
+        }
+        if self.games.get(game_id).to_move.get() != Uint::<8, 1>::from(color) {
+            return Err(GoError::NotYourTurn(NotYourTurn { gameId: game_id }));
+        }
         
-        for y_offset in 0..=1 {
-            for x_offset in 0..=1 {
-                let try_y = center.saturating_add(y_offset).min(BOARD_SIZE as u8 - 1);
-                let try_x = center.saturating_add(x_offset).min(BOARD_SIZE as u8 - 1);
-                
-                for (x, y) in &[(try_x, try_y), 
-                               (center.saturating_sub(x_offset), try_y),
-                               (try_x, center.saturating_sub(y_offset)),
-                               (center.saturating_sub(x_offset), center.saturating_sub(y_offset))] {
-                    if self.get_stone_at_position(board, *x, *y) == 0 && 
-                       !self.would_be_suicide(board, *x, *y, 2) &&
-                       !self.is_ko_violation(player, *x, *y) {
-                        contract_x = *x;
-                        contract_y = *y;
-                        found_move = true;
-                        break;
-                    }
-                }
-                
-                if found_move {
-                    break;
-                }
+        Ok(color)
+    }
+    
+    fn add_active_game(&mut self, player: Address, game_id: U256) {
+        let mut games = self.active_games.setter(player);
+        games.push(game_id);
+        let slot = U256::from(games.len());
+        self.active_game_slot.setter(player).insert(game_id, slot);
+    }
+    
+    fn archive_game(&mut self, player: Address, game_id: U256) {
+        self.remove_active_game(player, game_id);
+        if GAME_ARCHIVE {
+            self.finished_games.setter(player).push(game_id);
+        }
+    }
+    
+    fn remove_active_game(&mut self, player: Address, game_id: U256) {
+        let slot = self.active_game_slot.get(player).get(game_id);
+        if slot != U256::ZERO {
+            let index = slot.to::<usize>() - 1;
+            let mut games = self.active_games.setter(player);
+            let last = games.get(games.len() - 1).unwrap_or_default();
+            if let Some(mut moved) = games.setter(index) {
+                moved.set(last);
             }
+            games.erase_last();
             
-            if found_move {
-                break;
-            }
+            let mut slots = self.active_game_slot.setter(player);
+            slots.insert(last, slot);
+            slots.delete(game_id);
         }
-        
-        if !found_move {
-            for radius in 1..BOARD_SIZE as u8 {
-                for y in center.saturating_sub(radius)..=center.saturating_add(radius).min(BOARD_SIZE as u8 - 1) {
-                    for x in center.saturating_sub(radius)..=center.saturating_add(radius).min(BOARD_SIZE as u8 - 1) {
-                        if x == center.saturating_sub(radius) || 
-                           x == center.saturating_add(radius).min(BOARD_SIZE as u8 - 1) ||
-                           y == center.saturating_sub(radius) || 
-                           y == center.saturating_add(radius).min(BOARD_SIZE as u8 - 1) {
-                            
-                            if self.get_stone_at_position(board, x, y) == 0 && 
-                               !self.would_be_suicide(board, x, y, 2) &&
-                               !self.is_ko_violation(player, x, y) {
-                                contract_x = x;
-                                contract_y = y;
-                                found_move = true;
-                                break;
-                            }
-                        }
-                    }
-                    
-                    if found_move {
-                        break;
-                    }
-                }
-                
-                if found_move {
-                    break;
-                }
-            }
+    }
+    
+    fn is_bot_game(&self, game_id: U256) -> bool {
+        self.games.get(game_id).opponent.get() == Address::ZERO
+    }
+    
+    // Colours of the creator and of the opponent or bot. Games created before
+    // colours could be chosen have the creator on Black.
+    fn seat_colors(&self, game_id: U256) -> (u8, u8) {
+        let color = self.games.get(game_id).player_color.get().try_into().unwrap_or(BLACK);
+        if color == WHITE { (WHITE, BLACK) } else { (BLACK, WHITE) }
+    }
+    
+    // 1 for the creator's colour, 2 for the other one and 0 for no colour.
+    fn seat_of(&self, game_id: U256, color: u8) -> u8 {
+        if color == 0 {
+            0
+        } else if color == self.seat_colors(game_id).0 {
+            1
+        } else {
+            2
         }
-        
-        if !found_move {
-            for y in 0..BOARD_SIZE {
-                for x in 0..BOARD_SIZE {
-                    if self.get_stone_at_position(board, x as u8, y as u8) == 0 && 
-                       !self.would_be_suicide(board, x as u8, y as u8, 2) &&
-                       !self.is_ko_violation(player, x as u8, y as u8) {
-                        contract_x = x as u8;
-                        contract_y = y as u8;
-                        found_move = true;
-                        break;
-                    }
-                }
-                if found_move {
-                    break;
-                }
-            }
+    }
+    
+    // The opponent of a bot game is the zero address.
+    fn participant(&self, game_id: U256, color: u8) -> Address {
+        let game = self.games.get(game_id);
+        if self.seat_of(game_id, color) == 1 { game.player.get() } else { game.opponent.get() }
+    }
+    
+    // Returns 0 when the address is not part of the game.
+    fn player_color(&self, game_id: U256, participant: Address) -> u8 {
+        let game = self.games.get(game_id);
+        let (own, other) = self.seat_colors(game_id);
+        if participant == Address::ZERO {
+            0
+        } else if participant == game.player.get() {
+            own
+        } else if participant == game.opponent.get() {
+            other
+        } else {
+            0
         }
-        
-        if found_move {
-            let mut updated_board = self.set_stone_at_position(board, contract_x, contract_y, 2);
-            
-            let (board_after_capture, captured_stones, ko_x, ko_y) = 
-                self.capture_surrounded_stones(updated_board, contract_x, contract_y, 2);
-            updated_board = board_after_capture;
-            
-            let white_captures: u32 = self.white_captures.get(player).try_into().unwrap_or(0);
-            let black_captures: u32 = self.black_captures.get(player).try_into().unwrap_or(0) + captured_stones;
-            
-            self.contract_passed.insert(player, false);
-            
-            self.update_game(player, updated_board, white_captures, black_captures, ko_x, ko_y);
-            self.check_for_game_end(player);
+    }
+    
+    fn has_passed(&self, game_id: U256, color: u8) -> bool {
+        let game = self.games.get(game_id);
+        if color == BLACK { game.black_passed.get() } else { game.white_passed.get() }
+    }
+    
+    fn set_passed(&mut self, game_id: U256, color: u8, passed: bool) {
+        let mut game = self.games.setter(game_id);
+        if color == BLACK {
+            game.black_passed.set(passed);
         } else {
-            self.contract_passed.insert(player, true);
-            
-            if self.player_passed.get(player) {
-                self.end_game(player);
-            }
+            game.white_passed.set(passed);
+        }
+    }
+    
+    fn clear_ko(&mut self, game_id: U256) {
+        let mut game = self.games.setter(game_id);
+        game.ko_x.set(Uint::<8, 1>::from(NO_KO));
+        game.ko_y.set(Uint::<8, 1>::from(NO_KO));
+    }
+    
+    fn finish_turn(&mut self, game_id: U256, color: u8) -> Result<(), GoError> {
+        if self.is_bot_game(game_id) {
+            self.make_contract_move(game_id)
+        } else {
+            let next = if color == BLACK { WHITE } else { BLACK };
+            self.games.setter(game_id).to_move.set(Uint::<8, 1>::from(next));
+            self.check_for_game_end(game_id)
         }
     }
     
-    fn check_for_game_end(&mut self, player: Address) {
-        if self.player_passed.get(player) && self.contract_passed.get(player) {
-            self.end_game(player);
+    fn apply_move(&mut self, game_id: U256, position: &Position, x: u8, y: u8, stone_color: u8, outcome: MoveOutcome) {
+        let captured_stones = outcome.captured;
+        
+        self.set_passed(game_id, stone_color, false);
+        
+        self.store_position(game_id, position);
+        self.record_move(game_id, stone_color, x, y, captured_stones, position.board);
+        
+        events::emit(self.vm(), events::StonePlaced {
+            gameId: game_id,
+            player: self.participant(game_id, stone_color),
+            x,
+            y,
+            color: stone_color,
+            captured: captured_stones,
+        });
+    }
+    
+    fn apply_pass(&mut self, game_id: U256, color: u8) {
+        self.set_passed(game_id, color, true);
+        
+        let mut position = self.load_position(game_id);
+        position.pass(color);
+        self.store_position(game_id, &position);
+        self.record_move(game_id, color, NO_KO, NO_KO, 0, position.board);
+        
+        events::emit(self.vm(), events::Passed {
+            gameId: game_id,
+            player: self.participant(game_id, color),
+            color,
+        });
+    }
+    
+    fn record_move(&mut self, game_id: U256, color: u8, x: u8, y: u8, captured: u32, board: Board) {
+        if !MOVE_HISTORY {
             return;
         }
-        
-        let board = self.get_board(player);
-        let mut is_full = true;
-        
-        for y in 0..BOARD_SIZE {
-            for x in 0..BOARD_SIZE {
-                if self.get_stone_at_position(board, x as u8, y as u8) == 0 {
-                    is_full = false;
-                    break;
-                }
-            }
-            if !is_full {
-                break;
-            }
+        let board_hash = board.zobrist_hash();
+        let mut game = self.games.setter(game_id);
+        let mut entry = game.moves.grow();
+        entry.color.set(Uint::<8, 1>::from(color));
+        entry.x.set(Uint::<8, 1>::from(x));
+        entry.y.set(Uint::<8, 1>::from(y));
+        entry.captured.set(Uint::<32, 1>::from(captured));
+        entry.board_hash.set(Uint::<64, 1>::from(board_hash));
+    }
+    
+    fn rule_set_of(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).rule_set.get().try_into().unwrap_or(RULES_AREA)
+    }
+    
+    fn ko_rule_of(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).ko_rule.get().try_into().unwrap_or(KO_SIMPLE)
+    }
+    
+    fn handicap_of(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).handicap.get().try_into().unwrap_or(0)
+    }
+    
+    fn difficulty_of(&self, game_id: U256) -> u8 {
+        self.games.get(game_id).difficulty.get().try_into().unwrap_or(NO_DIFFICULTY)
+    }
+    
+    fn search_gas_of(&self, game_id: U256) -> u64 {
+        match self.games.get(game_id).search_gas.get().to::<u64>() {
+            0 => DEFAULT_SEARCH_GAS,
+            gas => gas,
         }
-        
-        if is_full {
-            self.end_game(player);
+    }
+    
+    // Earlier positions stay in storage; pass the game to `with_earlier`
+    // before trying moves under superko.
+    fn load_position(&self, game_id: U256) -> Position<'static> {
+        let game = self.games.get(game_id);
+        let ko_x = game.ko_x.get().try_into().unwrap_or(NO_KO);
+        let ko_y = game.ko_y.get().try_into().unwrap_or(NO_KO);
+        
+        Position {
+            board: self.load_board(game_id),
+            ko_rule: self.ko_rule_of(game_id),
+            ko: if ko_x == NO_KO { None } else { Some(Point::new(ko_x, ko_y)) },
+            captures: [
+                game.black_captures.get().try_into().unwrap_or(0),
+                game.white_captures.get().try_into().unwrap_or(0),
+            ],
+            history: Vec::new(),
+            earlier: None,
         }
     }
     
-    fn end_game(&mut self, player: Address) {
-        assert!(!self.game_ended.get(player), "Game already ended");
-        
-        let white_captures = self.white_captures.get(player).try_into().unwrap_or(0);
-        let black_captures = self.black_captures.get(player).try_into().unwrap_or(0);
-        
-        let player_points = self.player_points.get(player).try_into().unwrap_or(0);
-        
-        let new_points = if white_captures > black_captures {
-            player_points + 3
-        } else if white_captures < black_captures {
-            player_points + 1
-        } else {
-            player_points + 2
-        };
-        
-        self.update_player_points(player, new_points);
-        
-        self.game_boards.insert(player, Uint::<128, 2>::from(0u128));
-        self.white_captures.insert(player, Uint::<32, 1>::from(0u32));
-        self.black_captures.insert(player, Uint::<32, 1>::from(0u32));
-        self.last_move_x.insert(player, Uint::<8, 1>::from(0u8));
-        self.last_move_y.insert(player, Uint::<8, 1>::from(0u8));
-        self.player_passed.insert(player, false);
-        self.contract_passed.insert(player, false);
-        self.game_ended.insert(player, true);
+    // Only keys reached since the position was loaded are in its history.
+    fn store_position(&mut self, game_id: U256, position: &Position) {
+        self.store_board(game_id, position.board);
+        
+        let (ko_x, ko_y) = position.ko.map_or((NO_KO, NO_KO), |ko| (ko.x, ko.y));
+        let mut game = self.games.setter(game_id);
+        game.black_captures.set(Uint::<32, 1>::from(position.captures[0]));
+        game.white_captures.set(Uint::<32, 1>::from(position.captures[1]));
+        game.ko_x.set(Uint::<8, 1>::from(ko_x));
+        game.ko_y.set(Uint::<8, 1>::from(ko_y));
+        
+        if SUPERKO {
+            for key in &position.history {
+                game.seen_positions.insert(Uint::<64, 1>::from(*key), true);
+            }
+        }
     }
     
-    fn would_be_suicide(&self, board: u128, x: u8, y: u8, stone_color: u8) -> bool {
-        if self.would_capture_opponent_stones(board, x, y, stone_color) {
-            return false;
+    fn load_board(&self, game_id: U256) -> Board {
+        let game = self.games.get(game_id);
+        let mut words = [U256::ZERO; BOARD_WORDS];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = game.board.get(i).unwrap_or_default();
         }
-        let temp_board = self.set_stone_at_position(board, x, y, stone_color);
-        let liberties = self.count_liberties(temp_board, x, y);
-        liberties == 0
+        board_from_words(game.board_size.get().try_into().unwrap_or(0), words)
     }
     
-    fn would_capture_opponent_stones(&self, board: u128, x: u8, y: u8, stone_color: u8) -> bool {
-        let opponent_color = if stone_color == 1 { 2 } else { 1 };
-        let neighbors = [
-            (x + 1, y),     // Right
-            (x, y + 1),     // Down
-            (x.wrapping_sub(1), y), // Left
-            (x, y.wrapping_sub(1)), // Up
-        ];
-        
-        for (nx, ny) in neighbors.iter() {
-            if !self.is_valid_position(*nx, *ny) {
-                continue;
-            }
-            
-            let neighbor_stone = self.get_stone_at_position(board, *nx, *ny);
-            if neighbor_stone == opponent_color {
-                let opponent_liberties = self.count_liberties(board, *nx, *ny);
-                if opponent_liberties == 1 {
-                    let mut is_our_position_liberty = false;
-                    if self.get_stone_at_position(board, x, y) == 0 {
-                        for (check_x, check_y) in neighbors.iter() {
-                            if self.is_valid_position(*check_x, *check_y) && 
-                               *check_x == *nx && *check_y == *ny {
-                                is_our_position_liberty = true;
-                                break;
-                            }
-                        }
-                    }
-                    
-                    if is_our_position_liberty {
-                        return true;
-                    }
-                }
+    fn store_board(&mut self, game_id: U256, board: Board) {
+        let mut game = self.games.setter(game_id);
+        for (i, word) in board_words(&board).iter().enumerate() {
+            if let Some(mut slot) = game.board.setter(i) {
+                slot.set(*word);
             }
         }
-        
-        false
     }
     
-    fn capture_surrounded_stones(&mut self, board: u128, x: u8, y: u8, stone_color: u8) -> (u128, u32, u8, u8) {
-        let opponent_color = if stone_color == 1 { 2 } else { 1 };
-        let mut captured_count = 0;
-        let mut updated_board = board;
-        let mut ko_x = 0;
-        let mut ko_y = 0;
-        
-        let neighbors = [
-            (x + 1, y),     // Right
-            (x, y + 1),     // Down
-            (x.wrapping_sub(1), y), // Left
-            (x, y.wrapping_sub(1)), // Up
+    fn current_score(&self, game_id: U256) -> scoring::Score {
+        let board = self.load_board(game_id);
+        let game = self.games.get(game_id);
+        let rule_set = self.rule_set_of(game_id);
+        let prisoners = [
+            game.black_captures.get().try_into().unwrap_or(0),
+            game.white_captures.get().try_into().unwrap_or(0),
         ];
-        
-        for (nx, ny) in neighbors.iter() {
-            if !self.is_valid_position(*nx, *ny) {
-                continue;
+        let komi = game.komi.get().try_into().unwrap_or(0);
+        board.score(rule_set, prisoners, komi)
+    }
+    
+    fn make_contract_move(&mut self, game_id: U256) -> Result<(), GoError> {
+        let game = self.games.get(game_id);
+        let mut position = self.load_position(game_id).with_earlier(&*game);
+        let (human, bot) = self.seat_colors(game_id);
+        
+        // Only drawn for the bots that play at random.
+        let seed = || montecarlo::seed(
+            self.vm().block_number(),
+            self.vm().block_timestamp(),
+            game_id,
+            position.board.zobrist_hash(),
+            self.games.get(game_id).moves.len() as u32,
+        );
+        let choice = match self.difficulty_of(game_id) {
+            DIFFICULTY_RANDOM if RANDOM_BOT => bot::random_move(&position, bot, seed()),
+            DIFFICULTY_HEURISTIC if STRONG_BOTS => bot::heuristic_move(&position, bot),
+            DIFFICULTY_SEARCH if STRONG_BOTS => bot::search_move(&position, bot),
+            DIFFICULTY_MONTE_CARLO if MONTE_CARLO => {
+                let seed = seed();
+                // The search stops at whichever comes first: its budget
+                // spent, or the reserve for the rest of the call reached.
+                let ink_price = self.vm().tx_ink_price() as u64;
+                let budget = self.search_gas_of(game_id).saturating_mul(ink_price);
+                let reserve = MONTE_CARLO_GAS_RESERVE * ink_price;
+                let floor = self.vm().evm_ink_left().saturating_sub(budget).max(reserve);
+                let search = montecarlo::monte_carlo_move(
+                    &position,
+                    bot,
+                    self.rule_set_of(game_id),
+                    self.games.get(game_id).komi.get().try_into().unwrap_or(0),
+                    seed,
+                    MONTE_CARLO_PLAYOUTS,
+                    || self.vm().evm_ink_left() > floor,
+                );
+                events::emit(self.vm(), events::MonteCarloSearch {
+                    gameId: game_id,
+                    seed,
+                    playouts: search.playouts,
+                });
+                search.point
             }
+            _ => bot::spiral_move(&position, bot),
+        };
+        let played = choice
+            .and_then(|point| position.play(bot, point).ok().map(|outcome| (point, outcome)));
+        let position = position.without_earlier();
+        
+        if let Some((point, outcome)) = played {
+            self.apply_move(game_id, &position, point.x, point.y, bot, outcome);
+            self.check_for_game_end(game_id)
+        } else {
+            self.apply_pass(game_id, bot);
             
-            let neighbor_stone = self.get_stone_at_position(updated_board, *nx, *ny);
-            if neighbor_stone == opponent_color {
-                let liberties = self.count_liberties(updated_board, *nx, *ny);
-                
-                if liberties == 0 {
-                    let (new_board, stones_removed, removed_positions) = self.remove_group(updated_board, *nx, *ny);
-                    updated_board = new_board;
-                    captured_count += stones_removed;
-                    
-                    if stones_removed == 1 && !removed_positions.is_empty() {
-                        let captured_pos = removed_positions[0];
-                        ko_x = captured_pos.0;
-                        ko_y = captured_pos.1;
-                    }
-                }
+            if self.has_passed(game_id, human) {
+                self.end_game(game_id, 0)
+            } else {
+                Ok(())
             }
         }
-        
-        (updated_board, captured_count, ko_x, ko_y)
     }
     
-    fn remove_group(&self, board: u128, x: u8, y: u8) -> (u128, u32, Vec<(u8, u8)>) {
-        let stone_color = self.get_stone_at_position(board, x, y);
-        if stone_color == 0 {
-            return (board, 0, Vec::new());
+    fn check_for_game_end(&mut self, game_id: U256) -> Result<(), GoError> {
+        if self.has_passed(game_id, BLACK) && self.has_passed(game_id, WHITE) {
+            return self.end_game(game_id, 0);
         }
         
-        let mut visited: u128 = 0;
-        let mut group_stones: u128 = 0;
-        let mut count = 0;
-        let mut removed_positions = Vec::new();
+        if self.load_board(game_id).is_full() {
+            return self.end_game(game_id, 0);
+        }
+        Ok(())
+    }
+    
+    // A resigning colour loses regardless of the board; pass 0 to score it.
+    fn end_game(&mut self, game_id: U256, resigned_by: u8) -> Result<(), GoError> {
+        if self.games.get(game_id).ended.get() {
+            return Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id }));
+        }
         
-        let mut stack = Vec::with_capacity(BOARD_SIZE);
-        stack.push((x, y));
+        // From here on both are seats rather than colours.
+        let score = self.current_score(game_id);
+        let resigned_by = self.seat_of(game_id, resigned_by);
+        let winner = match resigned_by {
+            0 => self.seat_of(game_id, score.winner()),
+            1 => 2,
+            _ => 1,
+        };
+        let (player, opponent) = self.get_game_players(game_id);
+        let (own, other) = self.seat_colors(game_id);
+        
+        // Both players registered when the game began; this stamps last_played.
+        let (result, opposite) = match winner {
+            0 => (DRAW, DRAW),
+            1 => (WIN, LOSS),
+            _ => (LOSS, WIN),
+        };
+        self.register_player(player);
+        self.record_game(player, game_id, result);
+        if !self.is_bot_game(game_id) {
+            self.register_player(opponent);
+            self.record_game(opponent, game_id, opposite);
+        }
         
-        while let Some((curr_x, curr_y)) = stack.pop() {
-            let pos_idx = curr_y as usize * BOARD_SIZE + curr_x as usize;
-            let pos_bit = 1u128 << pos_idx;
-            
-            if visited & pos_bit != 0 {
-                continue;
-            }
-            
-            visited |= pos_bit;
-            
-            let curr_stone = self.get_stone_at_position(board, curr_x, curr_y);
-            if curr_stone != stone_color {
-                continue;
-            }
-            
-            group_stones |= pos_bit;
-            count += 1;
-            removed_positions.push((curr_x, curr_y));
-            
-            let directions = [
-                (curr_x + 1, curr_y),     // Right
-                (curr_x, curr_y + 1),     // Down
-                (curr_x.wrapping_sub(1), curr_y), // Left
-                (curr_x, curr_y.wrapping_sub(1)), // Up
-            ];
-            
-            for (nx, ny) in directions.iter() {
-                if self.is_valid_position(*nx, *ny) {
-                    let next_stone = self.get_stone_at_position(board, *nx, *ny);
-                    if next_stone == stone_color {
-                        stack.push((*nx, *ny));
-                    }
-                }
-            }
+        #[cfg(any(test, feature = "ratings"))]
+        self.update_ratings(game_id, winner);
+        self.archive_game(player, game_id);
+        if !self.is_bot_game(game_id) {
+            self.archive_game(opponent, game_id);
         }
         
-        let mut new_board = board;
+        self.clear_ko(game_id);
+        let mut game = self.games.setter(game_id);
+        game.black_passed.set(false);
+        game.white_passed.set(false);
+        game.ended.set(true);
+        game.winner.set(Uint::<8, 1>::from(winner));
+        game.resigned_by.set(Uint::<8, 1>::from(resigned_by));
+        
+        events::emit(self.vm(), events::GameEnded {
+            gameId: game_id,
+            winner,
+            playerScore: score.total(own),
+            opponentScore: score.total(other),
+            difficulty: self.difficulty_of(game_id),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "ratings"))]
+impl GoGame {
+    // Players are rated with Glicko-2, against the opponent or against the
+    // bot's rating for the game's difficulty; the bot's own rating follows
+    // Elo. Handicap stones count as extra rating for whoever played Black.
+    fn update_ratings(&mut self, game_id: U256, winner: u8) {
+        let (player, opponent) = self.get_game_players(game_id);
+        let difficulty = self.difficulty_of(game_id);
+        let bot_game = self.is_bot_game(game_id);
+        let player_glicko = self.glicko_of(player);
+        let opponent_glicko = if bot_game {
+            Glicko { rating: self.bot_rating_of(difficulty), deviation: BOT_DEVIATION, volatility: INITIAL_VOLATILITY }
+        } else {
+            self.glicko_of(opponent)
+        };
         
-        for y in 0..BOARD_SIZE {
-            for x in 0..BOARD_SIZE {
-                let pos_idx = y * BOARD_SIZE + x;
-                let pos_bit = 1u128 << pos_idx;
-                
-                if (group_stones & pos_bit) != 0 {
-                    new_board = self.set_stone_at_position(new_board, x as u8, y as u8, 0);
-                }
-            }
-        }
+        let bonus = self.handicap_of(game_id) as u32 * HANDICAP_STONE_RATING;
+        let (player_bonus, opponent_bonus) = if self.seat_colors(game_id).0 == BLACK { (bonus, 0) } else { (0, bonus) };
+        let (result, opposite) = match winner {
+            0 => (DRAW, DRAW),
+            1 => (WIN, LOSS),
+            _ => (LOSS, WIN),
+        };
+        // Each side sees the other's rating shifted by the handicap between them.
+        let seen = |glicko: Glicko, own_bonus: u32, other_bonus: u32| Glicko {
+            rating: (glicko.rating + own_bonus).saturating_sub(other_bonus),
+            ..glicko
+        };
         
-        (new_board, count, removed_positions)
+        let updated = player_glicko.update(&[(seen(opponent_glicko, opponent_bonus, player_bonus), result)]);
+        self.set_glicko(player, updated);
+        if bot_game {
+            let change = rating::rating_change(
+                opponent_glicko.rating + opponent_bonus,
+                player_glicko.rating + player_bonus,
+                opposite,
+            );
+            let bot_rating = rating::apply_change(opponent_glicko.rating, change);
+            self.bot_rating.insert(Uint::<8, 1>::from(difficulty), Uint::<32, 1>::from(bot_rating));
+            events::emit(self.vm(), events::BotRatingUpdated { difficulty, rating: bot_rating });
+        } else {
+            let updated = opponent_glicko.update(&[(seen(player_glicko, player_bonus, opponent_bonus), opposite)]);
+            self.set_glicko(opponent, updated);
+        }
     }
     
-    fn is_ko_violation(&self, player: Address, x: u8, y: u8) -> bool {
-        let last_x = self.last_move_x.get(player).try_into().unwrap_or(0);
-        let last_y = self.last_move_y.get(player).try_into().unwrap_or(0);
+    // Current rating with the deviation widened for every full rating period
+    // since the player's last rated game.
+    fn glicko_of(&self, player: Address) -> Glicko {
+        let deviation = self.player_deviation.get(player).try_into().unwrap_or(0);
+        let volatility = self.player_volatility.get(player).try_into().unwrap_or(0);
+        let glicko = Glicko {
+            rating: self.rating_of(player),
+            deviation: if deviation == 0 { INITIAL_DEVIATION } else { deviation },
+            volatility: if volatility == 0 { INITIAL_VOLATILITY } else { volatility },
+        };
         
-        if last_x == 0 && last_y == 0 {
-            return false;
+        let last_rated = self.player_last_rated.get(player).to::<u64>();
+        if last_rated == 0 {
+            return glicko;
         }
-        
-        x == last_x && y == last_y
+        glicko.decayed(self.vm().block_timestamp().saturating_sub(last_rated) / RATING_PERIOD)
     }
     
-    fn update_player_points(&mut self, player: Address, new_points: u32) {
-        let current_points = self.player_points.get(player).try_into().unwrap_or(0);
-        
-        if self.player_index.get(player) == Uint::<32, 1>::from(0) && current_points == 0 {
-            let index = self.total_players.get().try_into().unwrap_or(0) + 1;
-            self.player_index.insert(player, Uint::<32, 1>::from(index));
-            self.player_address_by_index.insert(Uint::<32, 1>::from(index), player);
-            self.total_players.set(Uint::<32, 1>::from(index));
+    // Unrated players start at INITIAL_RATING.
+    fn rating_of(&self, player: Address) -> u32 {
+        match self.player_rating.get(player).try_into().unwrap_or(0) {
+            0 => INITIAL_RATING,
+            rating => rating,
         }
-        
-        self.player_points.insert(player, Uint::<32, 1>::from(new_points));
     }
-}
\ No newline at end of file
+    
+    fn bot_rating_of(&self, difficulty: u8) -> u32 {
+        match self.bot_rating.get(Uint::<8, 1>::from(difficulty)).try_into().unwrap_or(0) {
+            0 => BOT_RATINGS.get(difficulty as usize).copied().unwrap_or(INITIAL_RATING),
+            rating => rating,
+        }
+    }
+    
+    fn set_glicko(&mut self, player: Address, glicko: Glicko) {
+        self.player_rating.insert(player, Uint::<32, 1>::from(glicko.rating));
+        self.player_deviation.insert(player, Uint::<32, 1>::from(glicko.deviation));
+        self.player_volatility.insert(player, Uint::<32, 1>::from(glicko.volatility));
+        let now = self.vm().block_timestamp();
+        self.player_last_rated.insert(player, Uint::<64, 1>::from(now));
+        
+        events::emit(self.vm(), events::RatingUpdated {
+            player,
+            rating: glicko.rating,
+            deviation: glicko.deviation,
+        });
+        self.leaderboard_set(player, glicko.conservative());
+    }
+}
+
+// Builds without the registry keep no profiles; see registry.rs.
+#[cfg(not(any(test, feature = "registry")))]
+impl GoGame {
+    fn register_player(&mut self, _player: Address) {}
+    
+    fn record_game(&mut self, _player: Address, _game_id: U256, _result: u32) {}
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stylus_sdk::testing::*;
+
+    // Storage slots are derived with the `native_keccak256` hostio, which only
+    // exists inside the Stylus VM, so host-side tests provide it themselves.
+    #[no_mangle]
+    unsafe extern "C" fn native_keccak256(bytes: *const u8, len: usize, output: *mut u8) {
+        let input = core::slice::from_raw_parts(bytes, len);
+        let hash = stylus_sdk::keccak_const::Keccak256::new().update(input).finalize();
+        core::ptr::copy_nonoverlapping(hash.as_ptr(), output, hash.len());
+    }
+
+    #[test]
+    fn illegal_moves_return_typed_errors() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+
+        assert_eq!(
+            game.create_game(8, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_HEURISTIC, BLACK, 0),
+            Err(GoError::UnsupportedBoardSize(UnsupportedBoardSize { boardSize: 8 }))
+        );
+        assert_eq!(
+            game.create_game(7, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_MONTE_CARLO + 1, BLACK, 0),
+            Err(GoError::UnknownDifficulty(UnknownDifficulty { difficulty: DIFFICULTY_MONTE_CARLO + 1 }))
+        );
+
+        let game_id = game.create_game(7, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_HEURISTIC, BLACK, 0).unwrap();
+        game.set_piece(game_id, 3, 3).unwrap();
+
+        assert_eq!(
+            game.set_piece(game_id, 3, 3),
+            Err(GoError::PositionOccupied(PositionOccupied { x: 3, y: 3 }))
+        );
+        assert_eq!(
+            game.set_piece(game_id, 7, 0),
+            Err(GoError::InvalidPosition(InvalidPosition { x: 7, y: 0 }))
+        );
+        assert_eq!(
+            game.pass_turn(U256::from(99)),
+            Err(GoError::NoActiveGame(NoActiveGame { gameId: U256::from(99) }))
+        );
+
+        let stranger = Address::repeat_byte(7);
+        vm.set_sender(stranger);
+        assert_eq!(
+            game.set_piece(game_id, 0, 0),
+            Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: stranger }))
+        );
+    }
+
+    #[test]
+    fn every_difficulty_answers_a_move() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        for difficulty in DIFFICULTY_RANDOM..=DIFFICULTY_MONTE_CARLO {
+            let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, difficulty, BLACK, 0).unwrap();
+            game.set_piece(game_id, 4, 4).unwrap();
+            assert_eq!(game.get_difficulty(game_id), difficulty);
+            assert_eq!(game.get_moves(game_id, 1, 1)[0].0, 2);
+            assert_ne!(game.get_moves(game_id, 1, 1)[0].1, NO_KO);
+        }
+    }
+
+    // A challenge from the default sender, already accepted by `opponent`,
+    // with the sender back on Black to move.
+    #[test]
+    fn superko_keys_are_looked_up_in_storage() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let game_id = game.create_game(7, RULES_AREA, KO_POSITIONAL_SUPERKO, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+        game.set_piece(game_id, 3, 3).unwrap();
+        let after_black = Board::empty(7).set_stone_at_position(3, 3, BLACK);
+
+        let stored = game.games.get(game_id);
+        let position = game.load_position(game_id).with_earlier(&*stored);
+        assert!(position.history.is_empty());
+        for board in [Board::empty(7), after_black, position.board] {
+            assert!(position.has_seen(board.zobrist_hash()));
+        }
+        assert!(!position.has_seen(after_black.set_stone_at_position(0, 0, WHITE).zobrist_hash()));
+    }
+
+    fn accepted_challenge(vm: &TestVM, game: &mut GoGame, opponent: Address, rule_set: u8, komi: u32) -> U256 {
+        let player = vm.msg_sender();
+        let game_id = game.create_challenge(opponent, 7, rule_set, KO_SIMPLE, komi, BLACK).unwrap();
+        vm.set_sender(opponent);
+        game.accept_challenge(game_id).unwrap();
+        vm.set_sender(player);
+        game_id
+    }
+
+    #[test]
+    fn games_are_decided_by_area_and_komi() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        // One stone each and no territory: komi alone decides.
+        for (komi, expected) in [(13, (2, 15, 2)), (0, (2, 2, 0))] {
+            let game_id = accepted_challenge(&vm, &mut game, opponent, RULES_AREA, komi);
+            game.set_piece(game_id, 0, 0).unwrap();
+            vm.set_sender(opponent);
+            game.set_piece(game_id, 6, 6).unwrap();
+            vm.set_sender(player);
+            game.pass_turn(game_id).unwrap();
+            vm.set_sender(opponent);
+            game.pass_turn(game_id).unwrap();
+            vm.set_sender(player);
+
+            assert!(game.is_game_ended(game_id));
+            assert_eq!(game.get_game_result(game_id), expected);
+        }
+
+        // A lone stone owns every empty point around it.
+        let game_id = accepted_challenge(&vm, &mut game, opponent, RULES_AREA, 13);
+        game.set_piece(game_id, 3, 3).unwrap();
+        vm.set_sender(opponent);
+        game.pass_turn(game_id).unwrap();
+        vm.set_sender(player);
+        game.pass_turn(game_id).unwrap();
+        assert_eq!(game.get_game_result(game_id), (98, 13, 1));
+    }
+
+    #[test]
+    fn territory_scoring_counts_prisoners_instead_of_stones() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+        assert_eq!(
+            game.create_challenge(opponent, 7, 2, KO_SIMPLE, 13, BLACK),
+            Err(GoError::UnknownRuleSet(UnknownRuleSet { ruleSet: 2 }))
+        );
+
+        let game_id = accepted_challenge(&vm, &mut game, opponent, RULES_TERRITORY, 13);
+        game.set_piece(game_id, 1, 0).unwrap();
+        vm.set_sender(opponent);
+        game.set_piece(game_id, 0, 0).unwrap();
+        vm.set_sender(player);
+        game.set_piece(game_id, 0, 1).unwrap();
+        vm.set_sender(opponent);
+        game.pass_turn(game_id).unwrap();
+        vm.set_sender(player);
+        game.pass_turn(game_id).unwrap();
+
+        assert_eq!(game.get_rule_set(game_id), RULES_TERRITORY);
+        assert_eq!(game.get_score(game_id), (RULES_TERRITORY, 47, 2, 1, 0, 0, 0, 13, 96, 13));
+        assert_eq!(game.get_game_result(game_id), (96, 13, 1));
+    }
+
+    #[test]
+    fn challengers_take_turns_once_accepted() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+        let stranger = Address::repeat_byte(5);
+        assert_eq!(
+            game.create_challenge(player, 7, RULES_AREA, KO_SIMPLE, 13, BLACK),
+            Err(GoError::InvalidOpponent(InvalidOpponent { opponent: player }))
+        );
+
+        let game_id = game.create_challenge(opponent, 7, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+        assert!(!game.is_game_active(game_id));
+        assert_eq!(game.set_piece(game_id, 3, 3), Err(GoError::NoActiveGame(NoActiveGame { gameId: game_id })));
+        assert_eq!(game.accept_challenge(game_id), Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: game_id })));
+
+        vm.set_sender(opponent);
+        game.accept_challenge(game_id).unwrap();
+        assert_eq!(game.get_player_color(game_id, opponent), WHITE);
+        assert_eq!(game.set_piece(game_id, 3, 3), Err(GoError::NotYourTurn(NotYourTurn { gameId: game_id })));
+
+        vm.set_sender(player);
+        game.set_piece(game_id, 3, 3).unwrap();
+        assert_eq!(game.get_turn(game_id), WHITE);
+        assert_eq!(game.pass_turn(game_id), Err(GoError::NotYourTurn(NotYourTurn { gameId: game_id })));
+
+        vm.set_sender(stranger);
+        assert_eq!(
+            game.set_piece(game_id, 2, 2),
+            Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: stranger }))
+        );
+
+        // Nobody answers for the opponent in a challenge.
+        vm.set_sender(opponent);
+        game.set_piece(game_id, 2, 2).unwrap();
+        assert_eq!(game.get_move_count(game_id), 2);
+        assert_eq!(game.get_turn(game_id), BLACK);
+    }
+
+    #[test]
+    fn games_move_from_active_to_finished_lists() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        let ids: Vec<U256> = (0..3)
+            .map(|_| game.create_game(7, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap())
+            .collect();
+        assert_eq!(ids, [U256::from(1), U256::from(2), U256::from(3)]);
+        let challenge = accepted_challenge(&vm, &mut game, opponent, RULES_AREA, 13);
+        assert_eq!(game.get_game_count(), challenge);
+        assert_eq!(game.get_active_games(player), [ids[0], ids[1], ids[2], challenge]);
+        assert_eq!(game.get_active_games(opponent), [challenge]);
+
+        // The last game takes the place of the one that ended.
+        game.resign(ids[0]).unwrap();
+        assert_eq!(game.get_active_games(player), [challenge, ids[1], ids[2]]);
+        game.resign(challenge).unwrap();
+        assert_eq!(game.get_active_games(player), [ids[2], ids[1]]);
+        assert!(game.get_active_games(opponent).is_empty());
+
+        assert_eq!(game.get_finished_games(player), [ids[0], challenge]);
+        assert_eq!(game.get_finished_games(opponent), [challenge]);
+        assert!(!game.is_game_active(ids[0]) && game.is_game_active(ids[1]));
+    }
+
+    #[test]
+    fn lifecycle_and_moves_are_logged() {
+        use stylus_sdk::alloy_sol_types::SolEvent;
+
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let game_id = game.create_game(7, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+        game.set_piece(game_id, 0, 0).unwrap();
+        game.resign(game_id).unwrap();
+
+        let logs = vm.get_emitted_logs();
+        let signatures: Vec<_> = logs.iter().map(|(topics, _)| topics[0]).collect();
+        assert_eq!(signatures, [
+            events::PlayerRegistered::SIGNATURE_HASH,
+            events::GameCreated::SIGNATURE_HASH,
+            events::StonePlaced::SIGNATURE_HASH,
+            events::StonePlaced::SIGNATURE_HASH,
+            events::Resigned::SIGNATURE_HASH,
+            events::RatingUpdated::SIGNATURE_HASH,
+            events::BotRatingUpdated::SIGNATURE_HASH,
+            events::GameEnded::SIGNATURE_HASH,
+        ]);
+
+        let (topics, data) = &logs[1];
+        let created = events::GameCreated::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!((created.gameId, created.player, created.opponent), (game_id, player, Address::ZERO));
+        assert_eq!((created.boardSize, created.komi, created.difficulty), (7, 13, DIFFICULTY_SPIRAL));
+
+        let (topics, data) = &logs[3];
+        let reply = events::StonePlaced::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        assert_eq!((reply.player, reply.x, reply.y, reply.color, reply.captured), (Address::ZERO, 3, 3, WHITE, 0));
+
+        let (topics, data) = &logs[7];
+        let ended = events::GameEnded::decode_raw_log(topics.iter().copied(), data, true).unwrap();
+        let (player_score, bot_score, winner) = game.get_game_result(game_id);
+        assert_eq!((ended.gameId, ended.winner), (game_id, winner));
+        assert_eq!((ended.playerScore, ended.opponentScore), (player_score, bot_score));
+    }
+
+    #[test]
+    fn ended_games_cannot_end_again() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let game_id = game.create_game(7, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+        game.resign(game_id).unwrap();
+
+        let ended = Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id }));
+        assert_eq!(game.end_game(game_id, 0), ended);
+        assert_eq!(game.resign(game_id), ended);
+        assert_eq!(game.pass_turn(game_id), ended);
+        assert_eq!(game.get_finished_games(vm.msg_sender()), [game_id]);
+    }
+
+    #[test]
+    fn challenges_can_be_cancelled_until_accepted() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        let withdrawn = game.create_challenge(opponent, 7, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+        let declined = game.create_challenge(opponent, 7, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+        assert_eq!(game.resign(withdrawn), Err(GoError::NoActiveGame(NoActiveGame { gameId: withdrawn })));
+        game.cancel_challenge(withdrawn).unwrap();
+        vm.set_sender(opponent);
+        game.cancel_challenge(declined).unwrap();
+
+        for game_id in [withdrawn, declined] {
+            assert_eq!(game.accept_challenge(game_id), Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: game_id })));
+            assert_eq!(game.cancel_challenge(game_id), Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: game_id })));
+            assert!(game.is_game_ended(game_id));
+        }
+        for account in [player, opponent] {
+            assert!(game.get_active_games(account).is_empty());
+            assert!(game.get_finished_games(account).is_empty());
+        }
+        assert_eq!(game.get_player_profile(player).0, 0);
+
+        // Accepted challenges and bot games are resigned instead.
+        vm.set_sender(player);
+        let accepted = accepted_challenge(&vm, &mut game, opponent, RULES_AREA, 13);
+        assert_eq!(game.cancel_challenge(accepted), Err(GoError::NoOpenChallenge(NoOpenChallenge { gameId: accepted })));
+        let bot_game = game.create_game(7, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+        assert_eq!(
+            game.cancel_challenge(bot_game),
+            Err(GoError::NotAPlayer(NotAPlayer { gameId: bot_game, account: player }))
+        );
+        vm.set_sender(Address::repeat_byte(5));
+        assert_eq!(
+            game.cancel_challenge(accepted),
+            Err(GoError::NotAPlayer(NotAPlayer { gameId: accepted, account: Address::repeat_byte(5) }))
+        );
+    }
+
+    #[test]
+    fn resigning_hands_the_game_to_the_other_seat() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        let game_id = accepted_challenge(&vm, &mut game, opponent, RULES_AREA, 13);
+        game.set_piece(game_id, 3, 3).unwrap();
+        vm.set_sender(opponent);
+        game.resign(game_id).unwrap();
+
+        assert_eq!((game.get_game_result(game_id).2, game.get_resigned_by(game_id)), (1, 2));
+        assert_eq!(game.get_player_profile(player).1, 1);
+        assert_eq!(game.get_player_profile(opponent).2, 1);
+        vm.set_sender(Address::repeat_byte(5));
+        assert_eq!(game.resign(game_id), Err(GoError::GameAlreadyEnded(GameAlreadyEnded { gameId: game_id })));
+    }
+
+    #[test]
+    fn monte_carlo_search_keeps_to_its_budget() {
+        use stylus_sdk::alloy_sol_types::SolEvent;
+
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_MONTE_CARLO, BLACK, 0).unwrap();
+        assert_eq!(game.get_search_gas(game_id), DEFAULT_SEARCH_GAS);
+        game.set_search_gas(game_id, 2_000_000).unwrap();
+        assert_eq!(game.get_search_gas(game_id), 2_000_000);
+        vm.set_sender(Address::repeat_byte(9));
+        assert_eq!(
+            game.set_search_gas(game_id, 0),
+            Err(GoError::NotAPlayer(NotAPlayer { gameId: game_id, account: Address::repeat_byte(9) }))
+        );
+        vm.set_sender(player);
+
+        // The test VM never burns ink, so with only the reserve left no
+        // playout runs, but the bot still answers.
+        vm.set_ink_left(MONTE_CARLO_GAS_RESERVE);
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_MONTE_CARLO, BLACK, 0).unwrap();
+        game.set_piece(game_id, 4, 4).unwrap();
+        assert_eq!(game.get_move_count(game_id), 2);
+
+        let (topics, data) = vm.get_emitted_logs().into_iter()
+            .rfind(|(topics, _)| topics[0] == events::MonteCarloSearch::SIGNATURE_HASH)
+            .unwrap();
+        let search = events::MonteCarloSearch::decode_raw_log(topics, &data, true).unwrap();
+        assert_eq!((search.gameId, search.playouts), (game_id, 0));
+    }
+
+    // Sizes MONTE_CARLO_GAS_RESERVE: the slots written by a bot reply plus
+    // the end of the game must stay within the reserved count.
+    #[test]
+    fn work_after_the_bot_move_fits_the_reserve() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let written = |before: &std::collections::HashMap<U256, stylus_sdk::alloy_primitives::B256>| {
+            vm.snapshot().storage.iter().filter(|(key, value)| before.get(key) != Some(value)).count() as u64
+        };
+
+        // Later games also move the player between leaderboard buckets.
+        for round in 0..3 {
+            vm.set_block_timestamp(1_000 + round * 10_000_000);
+            let game_id = game.create_game(19, RULES_AREA, KO_SITUATIONAL_SUPERKO, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+            let before = vm.snapshot().storage;
+            game.pass_turn(game_id).unwrap();
+            let reply = written(&before);
+            let before = vm.snapshot().storage;
+            game.resign(game_id).unwrap();
+            let ending = written(&before);
+            assert!(reply + ending <= crate::constants::MONTE_CARLO_RESERVED_SLOTS, "{reply} + {ending} slots");
+        }
+    }
+
+    #[test]
+    fn moves_are_recorded_and_paginated() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let game_id = game.create_game(7, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_HEURISTIC, BLACK, 0).unwrap();
+
+        game.set_piece(game_id, 3, 3).unwrap();
+        game.pass_turn(game_id).unwrap();
+        assert_eq!(game.get_move_count(game_id), 4);
+
+        let board_hash = game.load_board(game_id).zobrist_hash();
+        let moves = game.get_moves(game_id, 0, 10);
+        assert_eq!(moves.len(), 4);
+        assert_eq!((moves[0].0, moves[0].1, moves[0].2), (1, 3, 3));
+        assert_eq!(moves[1].0, 2);
+        assert_eq!(moves[2], (1, NO_KO, NO_KO, 0, moves[1].4));
+        assert_eq!(moves[3].4, board_hash);
+
+        assert_eq!(game.get_moves(game_id, 3, 10), vec![moves[3]]);
+        assert!(game.get_moves(game_id, 9, 10).is_empty());
+    }
+
+    // Reads a game back through the views, as an off-chain client would.
+    fn sgf_of(game: &GoGame, game_id: U256) -> String {
+        let (player, opponent) = game.get_game_players(game_id);
+        let (black, white) = if game.get_player_color(game_id, player) == WHITE {
+            (opponent, player)
+        } else {
+            (player, opponent)
+        };
+        let result = game.is_game_ended(game_id).then(|| {
+            let (player_total, opponent_total, winner) = game.get_game_result(game_id);
+            let color = match winner {
+                0 => 0,
+                1 => game.get_player_color(game_id, player),
+                _ => if game.get_player_color(game_id, player) == WHITE { BLACK } else { WHITE },
+            };
+            let resigned = game.get_resigned_by(game_id) != 0;
+            (color, (!resigned).then(|| player_total.abs_diff(opponent_total)))
+        });
+        sgf::render_sgf(&sgf::GameRecord {
+            board_size: game.get_board_size(game_id),
+            rule_set: game.get_rule_set(game_id),
+            komi: game.get_score(game_id).7,
+            handicap: game.get_handicap(game_id),
+            black,
+            white,
+            result,
+            setup: game.get_setup_board(game_id),
+            moves: game.get_moves(game_id, 0, game.get_move_count(game_id)),
+        })
+    }
+
+    #[test]
+    fn sgf_lists_setup_result_and_moves() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let game_id = game.create_game(9, RULES_TERRITORY, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, BLACK, 0).unwrap();
+
+        game.set_piece(game_id, 2, 3).unwrap();
+        let (_, bot_x, bot_y, _, _) = game.get_moves(game_id, 1, 1)[0];
+        game.pass_turn(game_id).unwrap();
+        game.resign(game_id).unwrap();
+
+        let bot_move = format!(";W[{}{}]", (b'a' + bot_x) as char, (b'a' + bot_y) as char);
+        let sgf = sgf_of(&game, game_id);
+        assert!(sgf.starts_with("(;FF[4]GM[1]CA[UTF-8]AP[stylish-go]SZ[9]KM[6.5]RU[Japanese]"));
+        assert!(sgf.contains(&format!("PB[{player:#x}]PW[stylish-go]RE[W+R]")));
+        assert!(sgf.contains(&format!(";B[cd]{bot_move};B[]")));
+        assert!(sgf.ends_with(')'));
+    }
+
+    #[test]
+    fn bot_opens_when_the_creator_plays_white() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        assert_eq!(
+            game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, 3, 0),
+            Err(GoError::UnknownColor(UnknownColor { color: 3 }))
+        );
+
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, WHITE, 0).unwrap();
+        assert_eq!(game.get_player_color(game_id, player), WHITE);
+        assert_eq!(game.get_turn(game_id), WHITE);
+        assert_eq!(game.get_moves(game_id, 0, 1)[0].0, BLACK);
+
+        game.set_piece(game_id, 0, 0).unwrap();
+        game.resign(game_id).unwrap();
+
+        let (player_score, bot_score, winner) = game.get_game_result(game_id);
+        assert_eq!((winner, game.get_resigned_by(game_id)), (2, 1));
+        assert_eq!(game.get_score(game_id).8, player_score);
+        assert!(player_score > bot_score);
+        assert!(sgf_of(&game, game_id).contains(&format!("PB[stylish-go]PW[{player:#x}]RE[B+R]")));
+    }
+
+    #[test]
+    fn handicap_stones_are_placed_and_white_opens() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        assert_eq!(
+            game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, BLACK, 6),
+            Err(GoError::UnsupportedHandicap(UnsupportedHandicap { handicap: 6, boardSize: 9 }))
+        );
+
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, BLACK, 4).unwrap();
+        assert_eq!(game.get_handicap(game_id), 4);
+        assert_eq!(game.get_moves(game_id, 0, 1)[0].0, WHITE);
+        assert_eq!(game.load_board(game_id).stones(BLACK).count(), 4);
+        assert_eq!(game.get_score(game_id).7, HANDICAP_KOMI + 8);
+        assert!(sgf_of(&game, game_id).contains("HA[4]"));
+        assert!(sgf_of(&game, game_id).contains("AB[cc][gc][cg][gg]"));
+    }
+
+    #[test]
+    fn ratings_move_against_the_bot_difficulty() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        assert_eq!(game.get_player_rank(player), 0);
+        vm.set_block_timestamp(1_000);
+
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_HEURISTIC, BLACK, 0).unwrap();
+        game.resign(game_id).unwrap();
+
+        // A new player's wide deviation makes one loss to a far weaker bot
+        // costly, while the bot gains its usual Elo amount.
+        let (rating, deviation, _) = game.get_player_glicko(player);
+        assert_eq!(rating, 958);
+        assert!(deviation < INITIAL_DEVIATION);
+        assert_eq!(game.get_player_rating(player), rating);
+        assert_eq!(game.get_bot_rating(DIFFICULTY_HEURISTIC), 1030);
+        assert_eq!(game.get_bot_rating(DIFFICULTY_SEARCH), BOT_RATINGS[DIFFICULTY_SEARCH as usize]);
+        assert_eq!(game.get_top_players(), vec![(player, rating - deviation / 500)]);
+        assert_eq!(game.get_player_rank(player), 1);
+
+        vm.set_block_timestamp(1_000 + 52 * RATING_PERIOD);
+        let (_, rested, _) = game.get_player_glicko(player);
+        assert!(rested > deviation);
+    }
+
+    #[test]
+    fn strong_players_can_resign_to_the_weakest_bot() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        game.set_glicko(player, Glicko { rating: 1900, deviation: 50_000, volatility: INITIAL_VOLATILITY });
+
+        let game_id = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_RANDOM, BLACK, 0).unwrap();
+        game.resign(game_id).unwrap();
+        assert_eq!(game.get_player_rating(player), 1885);
+        assert!(game.get_bot_rating(DIFFICULTY_RANDOM) > BOT_RATINGS[DIFFICULTY_RANDOM as usize]);
+    }
+
+    #[test]
+    fn players_are_rated_with_glicko_against_each_other() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        let game_id = game.create_challenge(opponent, 9, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+        vm.set_sender(opponent);
+        game.accept_challenge(game_id).unwrap();
+        game.resign(game_id).unwrap();
+
+        // Equal newcomers move symmetrically; the bot ratings are untouched.
+        let (winner, winner_deviation, _) = game.get_player_glicko(player);
+        let (loser, loser_deviation, _) = game.get_player_glicko(opponent);
+        assert!(winner > INITIAL_RATING && winner - INITIAL_RATING == INITIAL_RATING - loser);
+        assert_eq!(winner_deviation, loser_deviation);
+        assert!(winner_deviation < INITIAL_DEVIATION);
+        assert_eq!(game.get_player_rank(player), 1);
+        assert_eq!(game.get_player_rank(opponent), 2);
+    }
+
+    #[test]
+    fn leaderboard_ranks_and_pages_players_by_score() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+
+        let mut scores = Vec::new();
+        for i in 1..=40u8 {
+            let player = Address::repeat_byte(i);
+            let score = (i as u32 * 397) % 1800 + 300;
+            game.leaderboard_set(player, score);
+            scores.push((player, score));
+        }
+        // Moves within the board, to a tie and out of range.
+        for (i, score) in [(3, 2500), (7, 700), (8, 700), (11, 9000)] {
+            game.leaderboard_set(Address::repeat_byte(i), score);
+            scores[i as usize - 1].1 = score.min(leaderboard::MAX_LEADERBOARD_SCORE);
+        }
+
+        let mut expected = scores.clone();
+        expected.sort_by(|a, b| b.1.cmp(&a.1));
+        let page: Vec<u32> = game.get_leaderboard(0, 100).iter().map(|entry| entry.1).collect();
+        assert_eq!(page, expected.iter().map(|entry| entry.1).collect::<Vec<_>>());
+        assert_eq!(game.get_leaderboard(5, 10), game.get_leaderboard(0, 15)[5..].to_vec());
+        assert!(game.get_leaderboard(40, 10).is_empty());
+
+        for (player, score) in &scores {
+            let better = scores.iter().filter(|other| other.1 > *score).count() as u32;
+            assert_eq!(game.get_player_rank(*player), better + 1);
+        }
+        assert_eq!(game.get_player_rank(Address::repeat_byte(99)), 0);
+    }
+
+    #[test]
+    fn players_are_registered_once_and_profiled() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+        vm.set_block_timestamp(100);
+
+        game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_RANDOM, BLACK, 0).unwrap();
+        let game_id = game.create_challenge(opponent, 9, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+        assert_eq!((game.get_total_players(), game.is_registered(opponent)), (1, false));
+        vm.set_sender(opponent);
+        vm.set_block_timestamp(200);
+        game.accept_challenge(game_id).unwrap();
+        vm.set_block_timestamp(300);
+        game.resign(game_id).unwrap();
+
+        assert_eq!(game.get_players(0, 10), vec![player, opponent]);
+        assert_eq!(game.get_players(1, 10), vec![opponent]);
+        assert_eq!(game.get_player_profile(player), (1, 1, 0, 0, 100, 300));
+        assert_eq!(game.get_player_profile(opponent), (1, 0, 1, 0, 200, 300));
+    }
+
+    // The original contract's registry, written at its own slots: points at
+    // slot 8, addresses by index at slot 9 and total_players at slot 12. It
+    // left index 2 empty and counted four players for two.
+    #[test]
+    fn players_migrate_from_the_original_registry_in_batches() {
+        use stylus_sdk::alloy_primitives::{keccak256, B256};
+
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let legacy = Address::repeat_byte(7);
+        let mapping_slot = |key: B256, slot: u8| U256::from_be_bytes(keccak256([key.0, B256::with_last_byte(slot).0].concat()).0);
+        let by_index = |index: u8| mapping_slot(B256::with_last_byte(index), 9);
+        vm.set_storage(mapping_slot(legacy.into_word(), 8), B256::with_last_byte(3));
+        vm.set_storage(by_index(1), legacy.into_word());
+        vm.set_storage(by_index(3), player.into_word());
+        vm.set_storage(U256::from(12), B256::with_last_byte(4));
+
+        // Players already registered by a new game are not listed twice.
+        game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_RANDOM, BLACK, 0).unwrap();
+        assert_eq!(game.migrate_players(2), 2);
+        assert_eq!(game.migrate_players(10), 0);
+        assert_eq!(game.migrate_players(10), 0);
+        assert_eq!(game.get_players(0, 10), vec![player, legacy]);
+        assert_eq!(game.get_player_profile(legacy), (0, 0, 0, 0, 0, 0));
+        assert_eq!(game.get_player_points(legacy), 3);
+    }
+
+    #[test]
+    fn stats_follow_results_by_difficulty_and_board_size() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let player = vm.msg_sender();
+        let opponent = Address::repeat_byte(9);
+
+        let mut finished = Vec::new();
+        for (size, loser) in [(9, opponent), (13, opponent), (9, player)] {
+            vm.set_sender(player);
+            let game_id = game.create_challenge(opponent, size, RULES_AREA, KO_SIMPLE, 13, BLACK).unwrap();
+            vm.set_sender(opponent);
+            game.accept_challenge(game_id).unwrap();
+            vm.set_sender(player);
+            game.set_piece(game_id, 2, 2).unwrap();
+            vm.set_sender(loser);
+            game.resign(game_id).unwrap();
+            finished.push(game_id);
+        }
+        let bot_game = game.create_game(9, RULES_AREA, KO_SIMPLE, 13, DIFFICULTY_SPIRAL, BLACK, 0).unwrap();
+        game.resign(bot_game).unwrap();
+        finished.push(bot_game);
+
+        assert_eq!(game.get_player_profile(player).0, 4);
+        assert_eq!(game.get_player_stats(player), (0, 2, 0, 0));
+        assert_eq!(game.get_player_difficulty_record(player, NO_DIFFICULTY), (2, 1, 0));
+        assert_eq!(game.get_player_difficulty_record(player, DIFFICULTY_SPIRAL), (0, 1, 0));
+        assert_eq!(game.get_player_board_size_record(player, 9), (1, 2, 0));
+        assert_eq!(game.get_player_board_size_record(opponent, 13), (0, 1, 0));
+        assert_eq!(game.get_player_stats(opponent), (0, 1, 1, 1));
+
+        assert_eq!(game.get_finished_game_count(player), 4);
+        assert_eq!(game.get_finished_games_page(player, 1, 2), finished[1..3].to_vec());
+        assert_eq!(game.get_finished_games_page(opponent, 2, 5), vec![finished[2]]);
+    }
+
+    #[test]
+    fn games_can_start_from_sgf_setup() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let create = |game: &mut GoGame, sgf: &str| {
+            let setup = sgf::parse_setup(sgf).unwrap();
+            let words = sgf::setup_words(&setup).unwrap();
+            game.create_game_from_position(setup.size, words, setup.white_to_move, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_HEURISTIC, BLACK)
+        };
+
+        let game_id = create(&mut game, "(;GM[1]FF[4]SZ[9]AB[aa:ba][cc]AW[ab] C[a \\] comment];B[dd])").unwrap();
+        let board = game.get_board_as_array(game_id);
+        assert_eq!((board[0][0], board[0][1], board[2][2], board[1][0]), (1, 1, 1, 2));
+        assert_eq!(game.get_move_count(game_id), 0);
+        assert!(sgf_of(&game, game_id).contains("AB[aa][ba][cc]AW[ab]"));
+
+        assert_eq!(
+            create(&mut game, "(;SZ[9]AB[aa]AW[ab][ba]PL[W])"),
+            Err(GoError::StonesWithoutLiberties(StonesWithoutLiberties { x: 0, y: 0 }))
+        );
+
+        let game_id = create(&mut game, "(;SZ[9]AB[aa]AW[ab]PL[W])").unwrap();
+        assert_eq!(game.get_moves(game_id, 0, 1)[0].0, 2);
+
+        assert!(sgf::setup_words(&sgf::parse_setup("(;SZ[9]AB[aa][aa])").unwrap()).is_none());
+        assert!(sgf::parse_setup("(;SZ[9]AB[a])").is_none());
+    }
+
+    #[test]
+    fn setup_words_must_describe_a_board() {
+        let vm = TestVM::default();
+        let mut game = GoGame::from(&vm);
+        let mut create = |board_size: u8, setup: [U256; BOARD_WORDS]| {
+            game.create_game_from_position(board_size, setup, false, RULES_AREA, KO_SIMPLE, 0, DIFFICULTY_HEURISTIC, BLACK)
+        };
+
+        // Both bits of the first point, then a stone just off a 9x9 board.
+        assert_eq!(create(9, [U256::from(3), U256::ZERO, U256::ZERO]), Err(GoError::InvalidSetup(InvalidSetup {})));
+        assert_eq!(create(9, [U256::from(1) << 162, U256::ZERO, U256::ZERO]), Err(GoError::InvalidSetup(InvalidSetup {})));
+        assert_eq!(
+            create(8, [U256::ZERO; BOARD_WORDS]),
+            Err(GoError::UnsupportedBoardSize(UnsupportedBoardSize { boardSize: 8 }))
+        );
+        assert!(create(9, [U256::from(1) << 160, U256::ZERO, U256::ZERO]).is_ok());
+    }
+}
@@ -7,4 +7,5 @@ pub extern "C" fn main() {}
 #[cfg(feature = "export-abi")]
 fn main() {
     stylish_go::print_abi("MIT-OR-APACHE-2.0", "pragma solidity ^0.8.23;");
+    stylish_go::print_events_abi();
 }
@@ -0,0 +1,185 @@
+use alloc::vec::Vec;
+use stylus_sdk::{alloy_primitives::U256, crypto::keccak};
+
+use crate::bot::{is_true_eye, random_move, spiral_order};
+use crate::position::{Point, Position};
+use crate::zobrist::splitmix64;
+
+// Moves considered at the root; playouts are shared between them in turn.
+pub const MONTE_CARLO_CANDIDATES: usize = 16;
+
+// Playouts are cut off after this many moves and scored as they stand.
+pub const MAX_PLAYOUT_MOVES: u32 = 120;
+
+// SplitMix64 stream. Everything the bot draws comes from here, so a seed and
+// a position are enough to replay a search off-chain.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let value = splitmix64(self.0);
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        value
+    }
+}
+
+// Seed for the bot's move, derived from the block the move is mined in and
+// the state of the game before it.
+pub fn seed(block_number: u64, block_timestamp: u64, game_id: U256, board_hash: u64, move_count: u32) -> u64 {
+    let mut preimage = Vec::with_capacity(60);
+    preimage.extend_from_slice(&block_number.to_be_bytes());
+    preimage.extend_from_slice(&block_timestamp.to_be_bytes());
+    preimage.extend_from_slice(&game_id.to_be_bytes::<32>());
+    preimage.extend_from_slice(&board_hash.to_be_bytes());
+    preimage.extend_from_slice(&move_count.to_be_bytes());
+
+    let hash = keccak(&preimage);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Search {
+    pub point: Option<Point>,
+    pub playouts: u32,
+}
+
+// Plays random games from the position after each candidate and picks the
+// one with the best win rate, a draw counting as half a win. `keep_going` is
+// asked before every playout move so the caller can stop on its gas budget.
+// A playout cut short is dropped, and the number of complete ones is
+// returned: rerunning with that as `max_playouts` replays the search.
+pub fn monte_carlo_move(position: &Position,
+                        color: u8,
+                        rule_set: u8,
+                        komi: u32,
+                        seed: u64,
+                        max_playouts: u32,
+                        mut keep_going: impl FnMut() -> bool) -> Search {
+    let board = &position.board;
+    let candidates: Vec<Point> = spiral_order(board.size)
+        .into_iter()
+        .filter(|point| board.get_stone_at_position(point.x, point.y) == 0 && !is_true_eye(board, *point, color))
+        .filter(|point| position.is_legal(color, *point))
+        .take(MONTE_CARLO_CANDIDATES)
+        .collect();
+    if candidates.len() <= 1 {
+        return Search { point: candidates.first().copied(), playouts: 0 };
+    }
+
+    let opponent = if color == 1 { 2 } else { 1 };
+    let mut rng = Rng::new(seed);
+    let mut wins = Vec::from([0u32; MONTE_CARLO_CANDIDATES]);
+    let mut visits = wins.clone();
+    let mut playouts = 0;
+
+    while playouts < max_playouts && keep_going() {
+        let choice = playouts as usize % candidates.len();
+        // Playouts only avoid repeating their own positions, so that they
+        // never look up earlier ones in storage.
+        let mut game = position.clone().without_earlier();
+        if game.play(color, candidates[choice]).is_ok() {
+            match playout(&mut game, opponent, rule_set, komi, &mut rng, &mut keep_going) {
+                Some(winner) => wins[choice] += if winner == color { 2 } else if winner == 0 { 1 } else { 0 },
+                None => break,
+            }
+        }
+        visits[choice] += 1;
+        playouts += 1;
+    }
+
+    // Compares wins[a] / visits[a] against wins[b] / visits[b] without
+    // dividing; unvisited candidates never beat visited ones.
+    let mut best = 0;
+    for choice in 1..candidates.len() {
+        let better = if visits[best] == 0 {
+            visits[choice] > 0
+        } else {
+            visits[choice] > 0 && wins[choice] * visits[best] > wins[best] * visits[choice]
+        };
+        if better {
+            best = choice;
+        }
+    }
+
+    Search { point: Some(candidates[best]), playouts }
+}
+
+// Random moves for both sides until two passes in a row or the move limit,
+// then the winner under the game's own scoring; None if the budget ran out.
+fn playout(game: &mut Position,
+           mut to_move: u8,
+           rule_set: u8,
+           komi: u32,
+           rng: &mut Rng,
+           keep_going: &mut impl FnMut() -> bool) -> Option<u8> {
+    let mut passes = 0;
+    for _ in 0..MAX_PLAYOUT_MOVES {
+        if !keep_going() {
+            return None;
+        }
+        match random_move(game, to_move, rng.next_u64()) {
+            Some(point) if game.play(to_move, point).is_ok() => passes = 0,
+            _ => {
+                game.pass(to_move);
+                passes += 1;
+                if passes == 2 {
+                    break;
+                }
+            }
+        }
+        to_move = if to_move == 1 { 2 } else { 1 };
+    }
+
+    Some(game.board.score(rule_set, game.captures, komi).winner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::constants::{KO_SIMPLE, RULES_AREA};
+
+    // The first outputs of the reference SplitMix64 seeded with zero.
+    #[test]
+    fn rng_is_the_standard_splitmix64_stream() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+        assert_eq!(rng.next_u64(), 0x06c4_5d18_8009_454f);
+    }
+
+    #[test]
+    fn searches_replay_from_the_seed() {
+        let rows = [
+            ". . . . . . .",
+            ". . X . . . .",
+            ". X O X . . .",
+            ". . . . . . .",
+        ];
+        let position = Position::new(Board::from_rows(7, &rows), KO_SIMPLE, 2);
+        let first = monte_carlo_move(&position, 2, RULES_AREA, 13, 42, 48, || true);
+        assert_eq!(first.playouts, 48);
+        assert_eq!(monte_carlo_move(&position, 2, RULES_AREA, 13, 42, 48, || true), first);
+        assert!(position.is_legal(2, first.point.unwrap()));
+    }
+
+    #[test]
+    fn stops_when_the_budget_runs_out() {
+        let position = Position::new(Board::empty(9), KO_SIMPLE, 1);
+        // Budget checks happen inside playouts, so this runs out part way
+        // through one.
+        let mut allowed = 300;
+        let search = monte_carlo_move(&position, 1, RULES_AREA, 13, 7, 100, || {
+            allowed -= 1;
+            allowed >= 0
+        });
+        assert!(search.playouts > 0 && search.playouts < 100);
+        assert_eq!(search, monte_carlo_move(&position, 1, RULES_AREA, 13, 7, search.playouts, || true));
+    }
+}
@@ -0,0 +1,337 @@
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::constants::{KO_SIMPLE, KO_SITUATIONAL_SUPERKO, NO_KO};
+use crate::zobrist::SIDE_TO_MOVE_KEY;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point {
+    pub x: u8,
+    pub y: u8,
+}
+
+impl Point {
+    pub fn new(x: u8, y: u8) -> Self {
+        Point { x, y }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    InvalidPosition,
+    PositionOccupied,
+    KoViolation,
+    SuicideMove,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub captured: u32,
+    pub ko: Option<Point>,
+}
+
+// Keys of positions reached before a `Position` was loaded, kept where it is
+// cheaper to look up one key than to read them all, such as in storage.
+pub trait History {
+    fn contains(&self, key: u64) -> bool;
+}
+
+// A board plus everything the rules need to judge the next move. It has no
+// storage behind it, so it can be used off-chain and in plain unit tests.
+#[derive(Clone)]
+pub struct Position<'a> {
+    pub board: Board,
+    pub ko_rule: u8,
+    pub ko: Option<Point>,
+    // Stones captured by colour 1 and colour 2 respectively.
+    pub captures: [u32; 2],
+    // Keys of the positions reached, kept only under superko: in `earlier`
+    // if they came before the position was loaded, otherwise in `history`.
+    pub history: Vec<u64>,
+    pub earlier: Option<&'a dyn History>,
+}
+
+impl Position<'_> {
+    // `to_move` matters for the first superko key: handicap games and some
+    // SGF setups start with colour 2 to move.
+    pub fn new(board: Board, ko_rule: u8, to_move: u8) -> Position<'static> {
+        let mut position = Position {
+            board,
+            ko_rule,
+            ko: None,
+            captures: [0, 0],
+            history: Vec::new(),
+            earlier: None,
+        };
+        if ko_rule != KO_SIMPLE {
+            let last_mover = if to_move == 1 { 2 } else { 1 };
+            position.history.push(position.key(board, last_mover));
+        }
+        position
+    }
+
+    pub fn with_earlier(self, earlier: &dyn History) -> Position<'_> {
+        Position { earlier: Some(earlier), ..self.without_earlier() }
+    }
+
+    // Moves made from here on are only checked against `history`.
+    pub fn without_earlier(self) -> Position<'static> {
+        Position {
+            board: self.board,
+            ko_rule: self.ko_rule,
+            ko: self.ko,
+            captures: self.captures,
+            history: self.history,
+            earlier: None,
+        }
+    }
+
+    pub fn has_seen(&self, key: u64) -> bool {
+        self.history.contains(&key) || self.earlier.is_some_and(|earlier| earlier.contains(key))
+    }
+
+    // Under situational superko the same stones with a different player to
+    // move count as a different position.
+    pub fn key(&self, board: Board, last_mover: u8) -> u64 {
+        let hash = board.zobrist_hash();
+        if self.ko_rule == KO_SITUATIONAL_SUPERKO && last_mover == 1 {
+            hash ^ SIDE_TO_MOVE_KEY
+        } else {
+            hash
+        }
+    }
+
+    // Returns the board after the move without changing the position.
+    pub fn try_play(&self, color: u8, point: Point) -> Result<(Board, MoveOutcome), IllegalMove> {
+        let Point { x, y } = point;
+        if !self.board.is_valid_position(x, y) {
+            return Err(IllegalMove::InvalidPosition);
+        }
+        if self.board.get_stone_at_position(x, y) != 0 {
+            return Err(IllegalMove::PositionOccupied);
+        }
+        if self.ko_rule == KO_SIMPLE && self.ko == Some(point) {
+            return Err(IllegalMove::KoViolation);
+        }
+
+        // Same as `Board::would_be_suicide`, reusing the capture result.
+        let placed = self.board.set_stone_at_position(x, y, color);
+        let (board, captured, ko_x, ko_y) = placed.capture_surrounded_stones(x, y, color);
+        if captured == 0 && board.count_liberties(x, y) == 0 {
+            return Err(IllegalMove::SuicideMove);
+        }
+        if self.ko_rule != KO_SIMPLE && self.has_seen(self.key(board, color)) {
+            return Err(IllegalMove::KoViolation);
+        }
+
+        let ko = if ko_x == NO_KO { None } else { Some(Point::new(ko_x, ko_y)) };
+        Ok((board, MoveOutcome { captured, ko }))
+    }
+
+    pub fn is_legal(&self, color: u8, point: Point) -> bool {
+        self.try_play(color, point).is_ok()
+    }
+
+    pub fn play(&mut self, color: u8, point: Point) -> Result<MoveOutcome, IllegalMove> {
+        let (board, outcome) = self.try_play(color, point)?;
+
+        self.board = board;
+        self.ko = outcome.ko;
+        self.captures[color as usize - 1] += outcome.captured;
+        if self.ko_rule != KO_SIMPLE {
+            self.history.push(self.key(board, color));
+        }
+
+        Ok(outcome)
+    }
+
+    // Under situational superko a pass leaves a new position: the same
+    // stones with the other player to move.
+    pub fn pass(&mut self, color: u8) {
+        self.ko = None;
+        if self.ko_rule == KO_SITUATIONAL_SUPERKO {
+            self.history.push(self.key(self.board, color));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::KO_POSITIONAL_SUPERKO;
+
+    struct KoCase {
+        name: &'static str,
+        rows: &'static [&'static str],
+        mv: (u8, u8, u8),
+        ko: Option<(u8, u8)>,
+    }
+
+    const KO_CASES: &[KoCase] = &[
+        KoCase {
+            name: "single stone recapture in the centre",
+            rows: &[
+                ". . . . . . .",
+                ". . X O . . .",
+                ". X O . O . .",
+                ". . X O . . .",
+            ],
+            mv: (3, 2, 1),
+            ko: Some((2, 2)),
+        },
+        KoCase {
+            name: "single stone recapture at the top-left corner",
+            rows: &[
+                "O . O . . . .",
+                "X O . . . . .",
+            ],
+            mv: (1, 0, 1),
+            ko: Some((0, 0)),
+        },
+        KoCase {
+            name: "colour 2 recapturing on the right edge",
+            rows: &[
+                ". . . . . . .",
+                ". . . . . O X",
+                ". . . . O X .",
+                ". . . . . O X",
+            ],
+            mv: (6, 2, 2),
+            ko: Some((5, 2)),
+        },
+        KoCase {
+            name: "snapback capturing two stones",
+            rows: &[
+                "O O . O . . .",
+                "X X O . . . .",
+            ],
+            mv: (2, 0, 1),
+            ko: None,
+        },
+        KoCase {
+            name: "capturing stone connected to its group",
+            rows: &[
+                ". . . . . . .",
+                ". . X . . . .",
+                ". X O . X . .",
+                ". . X . . . .",
+            ],
+            mv: (3, 2, 1),
+            ko: None,
+        },
+        KoCase {
+            name: "capturing stone keeps two liberties",
+            rows: &[
+                ". . . . . . .",
+                ". . X . . . .",
+                ". X O . O . .",
+                ". . X O . . .",
+            ],
+            mv: (3, 2, 1),
+            ko: None,
+        },
+        KoCase {
+            name: "move without captures",
+            rows: &[],
+            mv: (3, 3, 1),
+            ko: None,
+        },
+    ];
+
+    #[test]
+    fn ko_point_is_recorded_only_for_single_stone_recapture() {
+        for case in KO_CASES {
+            let (x, y, color) = case.mv;
+            let mut position = Position::new(Board::from_rows(7, case.rows), KO_SIMPLE, color);
+            let outcome = position.play(color, Point::new(x, y)).unwrap();
+            assert_eq!(outcome.ko, case.ko.map(|(x, y)| Point::new(x, y)), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn ko_at_top_left_corner_is_enforced() {
+        let mut position = Position::new(Board::from_rows(7, &["O . O", "X O ."]), KO_SIMPLE, 1);
+        assert_eq!(position.play(1, Point::new(1, 0)).unwrap().captured, 1);
+        assert_eq!(position.captures, [1, 0]);
+
+        assert_eq!(position.play(2, Point::new(0, 0)), Err(IllegalMove::KoViolation));
+        assert!(position.is_legal(2, Point::new(3, 0)));
+
+        position.pass(2);
+        assert!(position.is_legal(2, Point::new(0, 0)));
+    }
+
+    #[test]
+    fn positional_superko_rejects_repeating_the_board() {
+        let rows = &["O . O", "X O ."];
+        let mut position = Position::new(Board::from_rows(7, rows), KO_POSITIONAL_SUPERKO, 1);
+        position.play(1, Point::new(1, 0)).unwrap();
+
+        // Retaking would recreate the starting board even after a pass.
+        position.pass(2);
+        position.pass(1);
+        assert_eq!(position.play(2, Point::new(0, 0)), Err(IllegalMove::KoViolation));
+    }
+
+    #[test]
+    fn situational_superko_starts_from_the_side_to_move() {
+        // Colour 2 opens by taking the ko, as it would after handicap
+        // stones; retaking recreates the start with colour 2 to move.
+        let rows = &[". X O", "X O ."];
+        let mut position = Position::new(Board::from_rows(7, rows), KO_SITUATIONAL_SUPERKO, 2);
+        position.play(2, Point::new(0, 0)).unwrap();
+        assert_eq!(position.play(1, Point::new(1, 0)), Err(IllegalMove::KoViolation));
+
+        let board = Board::from_rows(7, rows);
+        assert_eq!(Position::new(board, KO_SITUATIONAL_SUPERKO, 1).history, [board.zobrist_hash()]);
+        assert_eq!(position.history[0], board.zobrist_hash() ^ SIDE_TO_MOVE_KEY);
+    }
+
+    #[test]
+    fn situational_superko_remembers_passes() {
+        let rows = &[". X O", "X O ."];
+        let mut position = Position::new(Board::from_rows(7, rows), KO_SITUATIONAL_SUPERKO, 1);
+        position.pass(1);
+        position.play(2, Point::new(0, 0)).unwrap();
+        // Only the pass left this board with colour 2 to move.
+        assert_eq!(position.play(1, Point::new(1, 0)), Err(IllegalMove::KoViolation));
+
+        let mut position = Position::new(Board::from_rows(7, rows), KO_POSITIONAL_SUPERKO, 1);
+        position.pass(1);
+        assert_eq!(position.history.len(), 1);
+    }
+
+    struct Keys(Vec<u64>);
+
+    impl History for Keys {
+        fn contains(&self, key: u64) -> bool {
+            self.0.contains(&key)
+        }
+    }
+
+    #[test]
+    fn earlier_positions_are_looked_up_by_key() {
+        let start = Board::from_rows(7, &["O . O", "X O ."]);
+        let earlier = Keys(Vec::from([start.zobrist_hash()]));
+        let mut position = Position::new(start.set_stone_at_position(0, 0, 0), KO_POSITIONAL_SUPERKO, 1);
+        position.history.clear();
+
+        // Recreating `start` is only caught with the earlier keys.
+        assert!(position.is_legal(2, Point::new(0, 0)));
+        let mut position = position.with_earlier(&earlier);
+        assert_eq!(position.play(2, Point::new(0, 0)), Err(IllegalMove::KoViolation));
+        position.play(2, Point::new(3, 3)).unwrap();
+        assert_eq!(position.history.len(), 1);
+        assert!(position.without_earlier().is_legal(2, Point::new(0, 0)));
+    }
+
+    #[test]
+    fn illegal_moves_are_reported() {
+        let position = Position::new(Board::from_rows(7, &[". O", "O ."]), KO_SIMPLE, 1);
+
+        assert_eq!(position.try_play(1, Point::new(7, 0)), Err(IllegalMove::InvalidPosition));
+        assert_eq!(position.try_play(1, Point::new(1, 0)), Err(IllegalMove::PositionOccupied));
+        assert_eq!(position.try_play(1, Point::new(0, 0)), Err(IllegalMove::SuicideMove));
+        assert!(position.is_legal(2, Point::new(0, 0)));
+    }
+}
@@ -0,0 +1,72 @@
+// Elo ratings in whole points, used for the bot's per-difficulty ratings.
+// Results and expected scores are in thousandths, so updates stay in integer
+// arithmetic and give the same answer on every node.
+pub const INITIAL_RATING: u32 = 1500;
+pub const MIN_RATING: u32 = 100;
+pub const K_FACTOR: i64 = 32;
+
+// Starting ratings of the bot, indexed by difficulty.
+pub const BOT_RATINGS: [u32; 5] = [400, 700, 1000, 1300, 1600];
+
+// The bot plays at a steady strength, so players are rated against it as an
+// opponent with a small Glicko-2 deviation, in thousandths of a point.
+pub const BOT_DEVIATION: u32 = 60_000;
+
+// Rating equivalent of one handicap stone, credited to Black.
+pub const HANDICAP_STONE_RATING: u32 = 100;
+
+pub const WIN: u32 = 1000;
+pub const DRAW: u32 = 500;
+pub const LOSS: u32 = 0;
+
+// Expected score of the stronger side for rating differences of 0, 25, 50,
+// ... 800 points; larger differences use the last entry.
+const EXPECTED: [u32; 33] = [
+    500, 536, 571, 606, 640, 673, 703, 733, 760, 785, 808, 830, 849, 867, 882, 896, 909,
+    920, 930, 939, 947, 954, 960, 965, 969, 973, 977, 980, 983, 985, 987, 989, 990,
+];
+
+pub fn expected_score(rating: u32, opponent: u32) -> u32 {
+    let difference = rating.abs_diff(opponent).min(800);
+    let step = (difference / 25) as usize;
+    let low = EXPECTED[step];
+    let high = EXPECTED[(step + 1).min(EXPECTED.len() - 1)];
+    let stronger = low + (high - low) * (difference % 25) / 25;
+    if rating >= opponent { stronger } else { 1000 - stronger }
+}
+
+// Change to `rating` after scoring `score` against `opponent`, rounded to
+// the nearest point. The opponent moves by the same amount the other way.
+pub fn rating_change(rating: u32, opponent: u32, score: u32) -> i32 {
+    let scaled = K_FACTOR * (score as i64 - expected_score(rating, opponent) as i64);
+    let rounded = if scaled >= 0 { (scaled + 500) / 1000 } else { (scaled - 500) / 1000 };
+    rounded as i32
+}
+
+pub fn apply_change(rating: u32, change: i32) -> u32 {
+    rating.saturating_add_signed(change).max(MIN_RATING)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_scores_are_symmetric() {
+        assert_eq!(expected_score(1500, 1500), 500);
+        assert_eq!(expected_score(1700, 1500), 760);
+        assert_eq!(expected_score(1500, 1700), 240);
+        assert_eq!(expected_score(1510, 1500) + expected_score(1500, 1510), 1000);
+        assert_eq!(expected_score(3000, 100), 990);
+    }
+
+    #[test]
+    fn upsets_move_ratings_further() {
+        assert_eq!(rating_change(1500, 1500, WIN), 16);
+        assert_eq!(rating_change(1500, 1500, DRAW), 0);
+        assert_eq!(rating_change(1300, 1700, WIN), 29);
+        assert_eq!(rating_change(1700, 1300, WIN), 3);
+        assert_eq!(rating_change(1700, 1300, LOSS), -29);
+        assert_eq!(apply_change(110, -29), MIN_RATING);
+    }
+}
@@ -0,0 +1,120 @@
+use alloc::vec::Vec;
+
+use stylus_sdk::{
+    alloy_primitives::{Address, Uint, U256},
+    prelude::*,
+};
+
+use crate::constants::BLACK;
+use crate::rating::{DRAW, WIN};
+use crate::{events, GoGame, ResultCounts};
+
+// Every creator and opponent is registered when they first create or join a
+// game, and their profile keeps statistics over the games they finish.
+// Builds without the registry leave this module out.
+impl GoGame {
+    pub(crate) fn is_registered_player(&self, player: Address) -> bool {
+        self.player_profiles.get(player).index.get() != Uint::<32, 1>::ZERO
+    }
+
+    // Also stamps the profile with the current block.
+    pub(crate) fn register_player(&mut self, player: Address) {
+        if !self.is_registered_player(player) {
+            self.add_registered_player(player);
+        }
+
+        let now = Uint::<64, 1>::from(self.vm().block_timestamp());
+        let mut profile = self.player_profiles.setter(player);
+        if profile.first_played.get() == Uint::<64, 1>::ZERO {
+            profile.first_played.set(now);
+        }
+        profile.last_played.set(now);
+    }
+
+    fn add_registered_player(&mut self, player: Address) {
+        self.registered_players.push(player);
+        let index = self.registered_players.len() as u32;
+        self.player_profiles.setter(player).index.set(Uint::<32, 1>::from(index));
+        events::emit(self.vm(), events::PlayerRegistered { player, index });
+    }
+
+    // `result` is WIN, DRAW or LOSS from the player's side.
+    pub(crate) fn record_game(&mut self, player: Address, game_id: U256, result: u32) {
+        let color = self.player_color(game_id, player);
+        let difficulty = self.difficulty_of(game_id);
+        let game = self.games.get(game_id);
+        let board_size = game.board_size.get();
+        let captured = if color == BLACK { game.black_captures.get() } else { game.white_captures.get() };
+        let moves = Uint::<64, 1>::from(game.moves.len() as u64);
+
+        let one = Uint::<32, 1>::from(1u32);
+        let mut profile = self.player_profiles.setter(player);
+        let games_played = profile.games_played.get();
+        profile.games_played.set(games_played + one);
+        let total_captures = profile.captures.get();
+        profile.captures.set(total_captures + captured);
+        let total_moves = profile.total_moves.get();
+        profile.total_moves.set(total_moves + moves);
+
+        let streak = if result == WIN { profile.win_streak.get() + one } else { Uint::<32, 1>::ZERO };
+        profile.win_streak.set(streak);
+        if streak > profile.longest_win_streak.get() {
+            profile.longest_win_streak.set(streak);
+        }
+
+        let tally = match result {
+            WIN => &mut profile.wins,
+            DRAW => &mut profile.draws,
+            _ => &mut profile.losses,
+        };
+        let count = tally.get();
+        tally.set(count + one);
+        Self::count_result(&mut profile.by_difficulty.setter(Uint::<8, 1>::from(difficulty)), result);
+        Self::count_result(&mut profile.by_board_size.setter(board_size), result);
+    }
+
+    fn count_result(counts: &mut ResultCounts, result: u32) {
+        let tally = match result {
+            WIN => &mut counts.wins,
+            DRAW => &mut counts.draws,
+            _ => &mut counts.losses,
+        };
+        let count = tally.get();
+        tally.set(count + Uint::<32, 1>::from(1u32));
+    }
+
+    pub(crate) fn result_counts(counts: &ResultCounts) -> (u32, u32, u32) {
+        (
+            counts.wins.get().try_into().unwrap_or(0),
+            counts.losses.get().try_into().unwrap_or(0),
+            counts.draws.get().try_into().unwrap_or(0),
+        )
+    }
+
+    pub(crate) fn registered_page(&self, offset: u32, count: u32) -> Vec<Address> {
+        let total = self.registered_players.len();
+        let start = (offset as usize).min(total);
+        let end = start.saturating_add(count as usize).min(total);
+        (start..end).map(|i| self.registered_players.get(i).unwrap_or_default()).collect()
+    }
+
+    // Registers players of the original contract without waiting for their
+    // next game. It indexed everyone who finished a game there, but skipped
+    // indices and over-counted total_players, so empty indices are passed
+    // over. Their points cannot be split into wins, losses and draws and
+    // stay readable through get_player_points. Players who never finished
+    // a game there were not indexed and register when they next play.
+    pub(crate) fn migrate_legacy_players(&mut self, count: u32) -> u32 {
+        let total = self.total_players.get().to::<u32>();
+        let start = self.legacy_players_migrated.get().to::<u32>();
+        let end = start.saturating_add(count).min(total);
+        for index in start + 1..=end {
+            let player = self.player_address_by_index.get(Uint::<32, 1>::from(index));
+            if player != Address::ZERO && !self.is_registered_player(player) {
+                self.add_registered_player(player);
+            }
+        }
+        self.legacy_players_migrated.set(Uint::<32, 1>::from(end));
+        total - end
+    }
+}
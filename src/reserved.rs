@@ -0,0 +1,24 @@
+use core::marker::PhantomData;
+
+use stylus_sdk::{alloy_primitives::U256, host::VM, storage::StorageType};
+
+// Takes up the slots of `T` without giving access to them, for storage that
+// is no longer read or whose feature is not in the build. Fields after it
+// keep their place, while the contract skips the code that constructs `T`.
+pub struct Reserved<T>(PhantomData<T>);
+
+impl<T: StorageType + 'static> StorageType for Reserved<T> {
+    type Wraps<'a> = () where Self: 'a;
+    type WrapsMut<'a> = () where Self: 'a;
+
+    const SLOT_BYTES: usize = T::SLOT_BYTES;
+    const REQUIRED_SLOTS: usize = T::REQUIRED_SLOTS;
+
+    unsafe fn new(_slot: U256, _offset: u8, _host: VM) -> Self {
+        Reserved(PhantomData)
+    }
+
+    fn load<'s>(self) -> Self::Wraps<'s> {}
+
+    fn load_mut<'s>(self) -> Self::WrapsMut<'s> {}
+}
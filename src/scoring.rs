@@ -0,0 +1,84 @@
+use crate::board::{Board, Plane};
+use crate::constants::{BLACK, RULES_TERRITORY, WHITE};
+
+// All totals are in half points so that a fractional komi such as 6.5 can be
+// represented exactly and used to break ties. Per-colour values are indexed
+// by colour - 1: Black, who moves first, then White, who receives komi.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub rule_set: u8,
+    pub territory: [u32; 2],
+    pub stones: [u32; 2],
+    pub prisoners: [u32; 2],
+    pub komi: u32,
+}
+
+impl Score {
+    // Area scoring counts stones on the board, territory scoring counts
+    // prisoners instead.
+    pub fn total(&self, color: u8) -> u32 {
+        let i = color as usize - 1;
+        let extra = if self.rule_set == RULES_TERRITORY { self.prisoners[i] } else { self.stones[i] };
+        let komi = if color == WHITE { self.komi } else { 0 };
+        (self.territory[i] + extra) * 2 + komi
+    }
+
+    pub fn winner(&self) -> u8 {
+        match self.total(BLACK).cmp(&self.total(WHITE)) {
+            core::cmp::Ordering::Greater => BLACK,
+            core::cmp::Ordering::Less => WHITE,
+            core::cmp::Ordering::Equal => 0,
+        }
+    }
+}
+
+impl Board {
+    // Every stone on the board counts as alive, and an empty region is
+    // territory of a colour only if it borders that colour alone.
+    pub fn score(&self, rule_set: u8, prisoners: [u32; 2], komi: u32) -> Score {
+        let mut score = Score {
+            rule_set,
+            territory: [0, 0],
+            stones: [self.stones(BLACK).count(), self.stones(WHITE).count()],
+            prisoners,
+            komi,
+        };
+
+        // Each pass takes the first unscored empty point and floods its region.
+        let mut unscored = self.empty_points();
+        while let Some(index) = unscored.first() {
+            let mut seed = Plane::default();
+            seed.insert(index);
+            let region = self.flood_fill(seed, unscored);
+            unscored = unscored.and_not(region);
+
+            let border = self.neighbours(region);
+            let touches_black = !border.and(self.stones(BLACK)).is_empty();
+            let touches_white = !border.and(self.stones(WHITE)).is_empty();
+            match (touches_black, touches_white) {
+                (true, false) => score.territory[0] += region.count(),
+                (false, true) => score.territory[1] += region.count(),
+                _ => {}
+            }
+        }
+
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::RULES_AREA;
+
+    #[test]
+    fn regions_bordering_one_colour_are_territory() {
+        let rows = [". . X . O . ."; 7];
+        let board = Board::from_rows(7, &rows).set_stone_at_position(3, 6, 1);
+
+        let score = board.score(RULES_AREA, [0, 0], 13);
+        assert_eq!((score.territory, score.stones), ([14, 14], [8, 7]));
+        assert_eq!((score.total(BLACK), score.total(WHITE)), (44, 55));
+        assert_eq!(score.winner(), WHITE);
+    }
+}
@@ -0,0 +1,243 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+use stylus_sdk::alloy_primitives::{Address, U256};
+
+use crate::board::Board;
+use crate::constants::{BLACK, BOARD_WORDS, NO_KO, RULES_TERRITORY, WHITE};
+
+// The parts of an SGF root node used to set up a position.
+pub struct SgfSetup {
+    pub size: u8,
+    pub black: Vec<(u8, u8)>,
+    pub white: Vec<(u8, u8)>,
+    pub white_to_move: bool,
+}
+
+// Reads SZ, AB, AW and PL from the root node and ignores every other
+// property. Point lists may use the compressed "aa:cc" rectangle form.
+pub fn parse_setup(text: &str) -> Option<SgfSetup> {
+    let bytes = text.trim_start().as_bytes();
+    if !bytes.starts_with(b"(") {
+        return None;
+    }
+    
+    let mut setup = SgfSetup {
+        size: 19,
+        black: Vec::new(),
+        white: Vec::new(),
+        white_to_move: false,
+    };
+    
+    let mut pos = 1;
+    skip_whitespace(bytes, &mut pos);
+    if bytes.get(pos) != Some(&b';') {
+        return None;
+    }
+    pos += 1;
+    
+    loop {
+        skip_whitespace(bytes, &mut pos);
+        let start = pos;
+        while bytes.get(pos).is_some_and(|b| b.is_ascii_uppercase()) {
+            pos += 1;
+        }
+        if pos == start {
+            // Anything other than a property ends the root node.
+            break;
+        }
+        let ident = &bytes[start..pos];
+        
+        let mut values = Vec::new();
+        loop {
+            skip_whitespace(bytes, &mut pos);
+            if bytes.get(pos) != Some(&b'[') {
+                break;
+            }
+            values.push(read_value(bytes, &mut pos)?);
+        }
+        if values.is_empty() {
+            return None;
+        }
+        
+        match ident {
+            b"SZ" => setup.size = parse_number(&values[0])?,
+            b"AB" => parse_points(&values, &mut setup.black)?,
+            b"AW" => parse_points(&values, &mut setup.white)?,
+            b"PL" => setup.white_to_move = match values[0].as_slice() {
+                b"B" => false,
+                b"W" => true,
+                _ => return None,
+            },
+            _ => {}
+        }
+    }
+    
+    Some(setup)
+}
+
+fn skip_whitespace(bytes: &[u8], pos: &mut usize) {
+    while bytes.get(*pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+}
+
+// Reads a bracketed value starting at '[' and leaves `pos` after the ']'.
+fn read_value(bytes: &[u8], pos: &mut usize) -> Option<Vec<u8>> {
+    let mut value = Vec::new();
+    *pos += 1;
+    loop {
+        match *bytes.get(*pos)? {
+            b']' => break,
+            b'\\' => {
+                *pos += 1;
+                value.push(*bytes.get(*pos)?);
+            }
+            byte => value.push(byte),
+        }
+        *pos += 1;
+    }
+    *pos += 1;
+    Some(value)
+}
+
+fn parse_number(value: &[u8]) -> Option<u8> {
+    core::str::from_utf8(value).ok()?.trim().parse().ok()
+}
+
+fn parse_point(value: &[u8]) -> Option<(u8, u8)> {
+    match value {
+        [x, y] if x.is_ascii_lowercase() && y.is_ascii_lowercase() => Some((x - b'a', y - b'a')),
+        _ => None,
+    }
+}
+
+fn parse_points(values: &[Vec<u8>], points: &mut Vec<(u8, u8)>) -> Option<()> {
+    for value in values {
+        match value.iter().position(|b| *b == b':') {
+            Some(split) => {
+                let (x1, y1) = parse_point(&value[..split])?;
+                let (x2, y2) = parse_point(&value[split + 1..])?;
+                for y in y1.min(y2)..=y1.max(y2) {
+                    for x in x1.min(x2)..=x1.max(x2) {
+                        points.push((x, y));
+                    }
+                }
+            }
+            None => points.push(parse_point(value)?),
+        }
+    }
+    Some(())
+}
+
+fn sgf_color(color: u8) -> char {
+    if color == BLACK { 'B' } else { 'W' }
+}
+
+fn sgf_coordinate(value: u8) -> char {
+    (b'a' + value) as char
+}
+
+// Half points rendered as SGF reals, e.g. 13 becomes "6.5".
+fn half_points(value: u32) -> String {
+    if value % 2 == 0 {
+        format!("{}", value / 2)
+    } else {
+        format!("{}.5", value / 2)
+    }
+}
+
+fn player_name(player: Address) -> String {
+    if player == Address::ZERO {
+        String::from("stylish-go")
+    } else {
+        format!("{player:#x}")
+    }
+}
+
+// Packs the setup stones as `create_game_from_position` takes them, or None
+// if a stone is off the board or placed twice.
+pub fn setup_words(setup: &SgfSetup) -> Option<[U256; BOARD_WORDS]> {
+    let mut board = Board::empty(setup.size);
+    let stones = setup.black.iter().map(|point| (point, BLACK)).chain(setup.white.iter().map(|point| (point, WHITE)));
+    for (&(x, y), color) in stones {
+        if !board.is_valid_position(x, y) || board.get_stone_at_position(x, y) != 0 {
+            return None;
+        }
+        board = board.set_stone_at_position(x, y, color);
+    }
+    Some(crate::board_words(&board))
+}
+
+// A game as read through the contract's views, see `render_sgf`.
+pub struct GameRecord {
+    pub board_size: u8,
+    pub rule_set: u8,
+    // In half points, as `get_score` returns it.
+    pub komi: u32,
+    pub handicap: u8,
+    pub black: Address,
+    pub white: Address,
+    // For finished games, the winning colour or 0 for a draw, and the margin
+    // in half points or None if the loser resigned.
+    pub result: Option<(u8, Option<u32>)>,
+    // From `get_setup_board`.
+    pub setup: [U256; BOARD_WORDS],
+    // From `get_moves`.
+    pub moves: Vec<(u8, u8, u8, u32, u64)>,
+}
+
+// SGF is built off-chain from the views rather than by the contract, which
+// has no room for the formatting code.
+pub fn render_sgf(record: &GameRecord) -> String {
+    let rules = if record.rule_set == RULES_TERRITORY { "Japanese" } else { "Chinese" };
+    
+    let mut sgf = String::new();
+    let _ = write!(
+        sgf,
+        "(;FF[4]GM[1]CA[UTF-8]AP[stylish-go]SZ[{}]KM[{}]RU[{}]PB[{}]PW[{}]",
+        record.board_size,
+        half_points(record.komi),
+        rules,
+        player_name(record.black),
+        player_name(record.white),
+    );
+    if record.handicap > 0 {
+        let _ = write!(sgf, "HA[{}]", record.handicap);
+    }
+    
+    match record.result {
+        None => {}
+        Some((0, _)) => sgf.push_str("RE[0]"),
+        Some((color, margin)) => {
+            let margin = margin.map_or(String::from("R"), half_points);
+            let _ = write!(sgf, "RE[{}+{}]", sgf_color(color), margin);
+        }
+    }
+    
+    let setup = crate::board_from_words(record.board_size, record.setup);
+    for (property, color) in [("AB", BLACK), ("AW", WHITE)] {
+        let mut points = String::new();
+        for y in 0..setup.size {
+            for x in 0..setup.size {
+                if setup.get_stone_at_position(x, y) == color {
+                    let _ = write!(points, "[{}{}]", sgf_coordinate(x), sgf_coordinate(y));
+                }
+            }
+        }
+        if !points.is_empty() {
+            let _ = write!(sgf, "{property}{points}");
+        }
+    }
+    
+    for &(color, x, y, _, _) in &record.moves {
+        if x == NO_KO {
+            let _ = write!(sgf, ";{}[]", sgf_color(color));
+        } else {
+            let _ = write!(sgf, ";{}[{}{}]", sgf_color(color), sgf_coordinate(x), sgf_coordinate(y));
+        }
+    }
+    
+    sgf.push(')');
+    sgf
+}
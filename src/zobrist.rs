@@ -0,0 +1,34 @@
+use crate::board::Board;
+
+// Keys are derived from a fixed splitmix64 sequence so that hashes are
+// stable across deployments and can be reproduced off-chain. The bots draw
+// their random numbers from it too.
+pub(crate) const fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Computed when needed rather than kept in a table: the table would add
+// 5.8KB of incompressible data to the contract.
+fn stone_key(index: usize, color: u8) -> u64 {
+    splitmix64((2 * index as u64 + color as u64) << 32)
+}
+
+// Mixed into the hash when colour 2 is to move, for situational superko.
+pub const SIDE_TO_MOVE_KEY: u64 = splitmix64(u64::MAX);
+
+impl Board {
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0u64;
+
+        for color in [1, 2] {
+            for index in self.stones(color).indices() {
+                hash ^= stone_key(index, color);
+            }
+        }
+
+        hash
+    }
+}